@@ -1,50 +1,312 @@
+use std::collections::VecDeque;
+use std::env;
+use std::fmt;
 use std::io::prelude::*;
-use std::iter::IntoIterator;
+use std::str::FromStr;
 
 type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
 
 fn main() -> Result<()> {
-    let input = read_input()?;
+    let options = Options::from_args()?;
 
-    part1(&input);
-    part2(&input);
+    if options.stream {
+        let stdin = std::io::stdin();
+        return stream::<u64>(stdin.lock(), &options);
+    }
+
+    let input: Vec<u64> = read_input()?;
+
+    part1(&input, &options)?;
+    part2(&input, &options)?;
 
     Ok(())
 }
 
-fn read_input() -> Result<Vec<u16>> {
+struct Options {
+    window: usize,
+    jumps: usize,
+    /// Process stdin a line at a time instead of reading it all up front.
+    stream: bool,
+}
+
+impl Options {
+    fn from_args() -> Result<Options> {
+        let mut options = Options {
+            window: 3,
+            jumps: 3,
+            stream: false,
+        };
+        let mut args = env::args().skip(1);
+        while let Some(arg) = args.next() {
+            if arg == "--stream" {
+                options.stream = true;
+                continue;
+            }
+            let value = args.next().ok_or(format!("missing value for {}", arg))?;
+            match arg.as_str() {
+                "--window" => options.window = value.parse()?,
+                "--jumps" => options.jumps = value.parse()?,
+                _ => return Err(format!("unknown option: {}", arg).into()),
+            }
+        }
+        if options.window == 0 {
+            return Err("window size must be at least 1".into());
+        }
+        Ok(options)
+    }
+}
+
+fn read_input<T: Depth>() -> Result<Vec<T>> {
     let mut input = String::new();
     std::io::stdin().read_to_string(&mut input)?;
-    Ok(input
+    input
         .lines()
-        .map(|line| line.parse::<u16>().expect("invalid number"))
-        .collect())
+        .enumerate()
+        .map(|(i, line)| parse_depth(line, i + 1))
+        .collect()
+}
+
+fn parse_depth<T: Depth>(line: &str, line_num: usize) -> Result<T> {
+    line.trim()
+        .parse::<T>()
+        .map_err(|_| format!("line {}: invalid number: {:?}", line_num, line).into())
+}
+
+/// A numeric reading type. Window sums use `checked_add` so that deep
+/// readings report an overflow instead of wrapping.
+trait Depth: Copy + Ord + FromStr + fmt::Display {
+    fn zero() -> Self;
+    fn checked_add(self, other: Self) -> Option<Self>;
+    fn checked_sub(self, other: Self) -> Option<Self>;
+}
+
+macro_rules! impl_depth {
+    ($($t:ty),*) => {
+        $(
+            impl Depth for $t {
+                fn zero() -> Self {
+                    0
+                }
+
+                fn checked_add(self, other: Self) -> Option<Self> {
+                    <$t>::checked_add(self, other)
+                }
+
+                fn checked_sub(self, other: Self) -> Option<Self> {
+                    <$t>::checked_sub(self, other)
+                }
+            }
+        )*
+    };
+}
+
+impl_depth!(u16, u32, u64, u128, i32, i64, i128);
+
+fn window_sums<T: Depth>(input: &[T], size: usize) -> Result<Vec<T>> {
+    input
+        .windows(size)
+        .enumerate()
+        .map(|(i, w)| {
+            w.iter()
+                .try_fold(T::zero(), |acc, &v| acc.checked_add(v))
+                .ok_or_else(|| format!("window sum starting at reading {} overflows", i + 1).into())
+        })
+        .collect()
+}
+
+/// The last `size` readings and their running sum.
+struct RingWindow<T> {
+    values: VecDeque<T>,
+    size: usize,
+    sum: T,
 }
 
-fn part1(input: &[u16]) {
-    let num_increases = count_increases(input.iter().cloned());
+impl<T: Depth> RingWindow<T> {
+    fn new(size: usize) -> RingWindow<T> {
+        RingWindow {
+            values: VecDeque::with_capacity(size),
+            size,
+            sum: T::zero(),
+        }
+    }
+
+    /// Adds a reading, returning the window sum once the window is full.
+    fn push(&mut self, value: T) -> Result<Option<T>> {
+        if self.values.len() == self.size {
+            let oldest = self.values.pop_front().unwrap();
+            self.sum = self
+                .sum
+                .checked_sub(oldest)
+                .ok_or("window sum underflows")?;
+        }
+        self.values.push_back(value);
+        self.sum = self.sum.checked_add(value).ok_or("window sum overflows")?;
 
-    println!("part1: {}", num_increases);
+        if self.values.len() == self.size {
+            Ok(Some(self.sum))
+        } else {
+            Ok(None)
+        }
+    }
 }
 
-fn part2(input: &[u16]) {
-    let window_sums = input.windows(3).map(|w| w[0] + w[1] + w[2]);
-    let num_increases = count_increases(window_sums);
+#[derive(Clone, Copy)]
+struct Jump<T> {
+    /// Position of the value the jump lands on, counting from 1 like input
+    /// lines.
+    index: usize,
+    from: T,
+    to: T,
+    size: T,
+}
 
-    println!("part2: {}", num_increases);
+impl<T: Depth> fmt::Display for Jump<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Unchanged readings are never recorded as jumps.
+        let sign = if self.to > self.from { '+' } else { '-' };
+        write!(
+            f,
+            "#{}: {} -> {} ({}{})",
+            self.index, self.from, self.to, sign, self.size
+        )
+    }
 }
 
-fn count_increases(input: impl IntoIterator<Item = u16>) -> u32 {
-    let mut iter = input.into_iter();
-    let mut last_depth = iter.next().unwrap();
-    let mut num_increases = 0;
+struct SequenceStats<T> {
+    increases: usize,
+    decreases: usize,
+    longest_increasing_run: usize,
+    biggest_jumps: Vec<Jump<T>>,
+    max_jumps: usize,
+    current_run: usize,
+    count: usize,
+    last: Option<T>,
+}
 
-    for depth in iter {
-        if depth > last_depth {
-            num_increases += 1;
+impl<T: Depth> SequenceStats<T> {
+    fn new(max_jumps: usize) -> SequenceStats<T> {
+        SequenceStats {
+            increases: 0,
+            decreases: 0,
+            longest_increasing_run: 0,
+            biggest_jumps: Vec::with_capacity(max_jumps + 1),
+            max_jumps,
+            current_run: 0,
+            count: 0,
+            last: None,
         }
-        last_depth = depth;
     }
 
-    num_increases
+    fn from_values(values: impl IntoIterator<Item = T>, max_jumps: usize) -> Result<Self> {
+        let mut stats = SequenceStats::new(max_jumps);
+        for value in values {
+            stats.push(value)?;
+        }
+        Ok(stats)
+    }
+
+    fn push(&mut self, value: T) -> Result<()> {
+        self.count += 1;
+        let index = self.count;
+
+        if let Some(last) = self.last {
+            let size = if value >= last {
+                value.checked_sub(last)
+            } else {
+                last.checked_sub(value)
+            };
+            let size = size.ok_or_else(|| format!("jump to value {} overflows", index))?;
+
+            if value > last {
+                self.increases += 1;
+                self.current_run += 1;
+            } else {
+                if value < last {
+                    self.decreases += 1;
+                }
+                self.current_run = 1;
+            }
+
+            if value != last {
+                self.record_jump(Jump {
+                    index,
+                    from: last,
+                    to: value,
+                    size,
+                });
+            }
+        } else {
+            self.current_run = 1;
+        }
+
+        self.longest_increasing_run = self.longest_increasing_run.max(self.current_run);
+        self.last = Some(value);
+        Ok(())
+    }
+
+    /// Keeps the `max_jumps` biggest jumps, biggest first, with the earliest
+    /// jump winning ties.
+    fn record_jump(&mut self, jump: Jump<T>) {
+        let pos = self
+            .biggest_jumps
+            .iter()
+            .position(|j| jump.size > j.size)
+            .unwrap_or(self.biggest_jumps.len());
+        if pos < self.max_jumps {
+            self.biggest_jumps.insert(pos, jump);
+            self.biggest_jumps.truncate(self.max_jumps);
+        }
+    }
+
+    fn print(&self, label: &str) {
+        println!("{}: {}", label, self.increases);
+        println!("{}: increases = {}", label, self.increases);
+        println!("{}: decreases = {}", label, self.decreases);
+        println!(
+            "{}: longest increasing run = {}",
+            label, self.longest_increasing_run
+        );
+        for jump in self.biggest_jumps.iter() {
+            println!("{}: big jump {}", label, jump);
+        }
+    }
+}
+
+fn part1<T: Depth>(input: &[T], options: &Options) -> Result<()> {
+    let stats = SequenceStats::from_values(input.iter().cloned(), options.jumps)?;
+    stats.print("part1");
+
+    Ok(())
+}
+
+fn part2<T: Depth>(input: &[T], options: &Options) -> Result<()> {
+    let window_sums = window_sums(input, options.window)?;
+    let stats = SequenceStats::from_values(window_sums, options.jumps)?;
+    stats.print("part2");
+
+    Ok(())
+}
+
+/// Computes both parts in a single pass over `input`, holding only the
+/// current window of readings in memory.
+fn stream<T: Depth>(input: impl BufRead, options: &Options) -> Result<()> {
+    let mut part1 = SequenceStats::new(options.jumps);
+    let mut part2 = SequenceStats::new(options.jumps);
+    let mut window = RingWindow::new(options.window);
+
+    for (i, line) in input.lines().enumerate() {
+        let depth: T = parse_depth(&line?, i + 1)?;
+        part1.push(depth)?;
+        let sum = window
+            .push(depth)
+            .map_err(|e| format!("line {}: {}", i + 1, e))?;
+        if let Some(sum) = sum {
+            part2.push(sum)?;
+        }
+    }
+
+    part1.print("part1");
+    part2.print("part2");
+
+    Ok(())
 }
@@ -1,4 +1,4 @@
-use shared::Grid;
+use shared::{Automaton, Grid, Highlight, Neighbourhood};
 use std::env;
 use std::fs::File;
 use std::io::prelude::*;
@@ -6,17 +6,43 @@ use std::io::prelude::*;
 type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
 
 fn main() -> Result<()> {
-    let input = read_input()?;
+    let options = Options::from_args()?;
+    let input = read_input(options.path.as_deref())?;
 
-    part1(&input);
+    part1(&input, options.trace);
     part2(&input);
 
     Ok(())
 }
 
-fn read_input() -> Result<Grid<u8>> {
+const USAGE: &str = "usage: day11 [--trace] [<input>]";
+
+struct Options {
+    /// Print the grid after every part1 step, highlighting what changed.
+    trace: bool,
+    path: Option<String>,
+}
+
+impl Options {
+    fn from_args() -> Result<Options> {
+        let mut options = Options {
+            trace: false,
+            path: None,
+        };
+        for arg in env::args().skip(1) {
+            match arg.as_str() {
+                "--trace" => options.trace = true,
+                _ if options.path.is_none() && !arg.starts_with('-') => options.path = Some(arg),
+                _ => return Err(USAGE.into()),
+            }
+        }
+        Ok(options)
+    }
+}
+
+fn read_input(path: Option<&str>) -> Result<Grid<u8>> {
     let mut input = String::new();
-    if let Some(filename) = env::args().skip(1).nth(0) {
+    if let Some(filename) = path {
         let mut r = File::open(filename)?;
         r.read_to_string(&mut input)?;
     } else {
@@ -37,103 +63,46 @@ fn read_input() -> Result<Grid<u8>> {
     Ok(g)
 }
 
-fn reset_flashed(g: &mut Grid<u8>) {
-    for y in 0..g.height() {
-        for x in 0..g.width() {
-            if g[(x, y)] >= 10 {
-                g[(x, y)] = 0;
-            }
-        }
-    }
-}
-
-fn increment(g: &mut Grid<u8>, x: usize, y: usize) -> usize {
-    let mut flashes = 0;
-
-    g[(x, y)] += 1;
-
-    if g[(x, y)] == 10 {
-        flashes += 1;
-
-        let x = x as isize;
-        let y = y as isize;
-        for yy in (y - 1)..=(y + 1) {
-            for xx in (x - 1)..=(x + 1) {
-                if xx >= 0 && (xx as usize) < g.width() && yy >= 0 && (yy as usize) < g.height() {
-                    flashes += increment(g, xx as usize, yy as usize);
-                }
-            }
-        }
-    }
-
-    flashes
+/// Each step every octopus gains 1 energy; any above 9 flashes, giving 1
+/// energy to all 8 neighbours, and every octopus that flashed resets to 0.
+fn octopuses(input: &Grid<u8>) -> Automaton<'static, u8> {
+    Automaton::new(input.clone(), |g, x, y| g[(x, y)] + 1).with_cascade(
+        Neighbourhood::Moore,
+        |&v| v > 9,
+        |v| *v += 1,
+        |v| *v = 0,
+    )
 }
 
-fn dump(g: &Grid<u8>) {
-    for y in 0..g.height() {
-        for x in 0..g.width() {
-            let v = g[(x, y)];
-            if v >= 10 {
-                print!("*");
-            } else {
-                print!("{}", v)
-            }
-        }
-        println!("")
-    }
+fn dump(prev: &Grid<u8>, g: &Grid<u8>) {
+    println!(
+        "{}",
+        prev.render_diff(g, Highlight::Ansi, |v| v.to_string())
+    );
 }
 
-fn part1(input: &Grid<u8>) {
-    let mut g = input.clone();
-    let mut total_flashes = 0;
-
-    let step_count = 100;
-
-    for step in 1..=step_count {
-        // println!("\nstep {} start:", i);
-        // dump(input);
+fn part1(input: &Grid<u8>, trace: bool) {
+    let mut octopuses = octopuses(input);
 
-        for y in 0..g.height() {
-            for x in 0..g.width() {
-                total_flashes += increment(&mut g, x, y);
-            }
-        }
-
-        reset_flashed(&mut g);
-
-        // println!("\nstep {} end:", i);
-        // dump(&g);
+    if trace {
+        let mut prev = input.clone();
+        octopuses = octopuses.on_step(move |stats, g| {
+            println!("\nstep {} end:", stats.step);
+            dump(&prev, g);
+            prev = g.clone();
+        });
     }
 
+    let total_flashes: usize = octopuses.run(100).iter().map(|s| s.fired).sum();
+
     println!("\npart1: result = {}", total_flashes);
 }
 
 fn part2(input: &Grid<u8>) {
-    let mut g = input.clone();
+    let num_octopuses = input.width() * input.height();
+    let mut octopuses = octopuses(input);
 
-    let mut step_count = 1;
-
-    loop {
-        // println!("\nstep {} start:", i);
-        // dump(input);
-
-        for y in 0..g.height() {
-            for x in 0..g.width() {
-                increment(&mut g, x, y);
-            }
-        }
-
-        if g.iter().all(|(_, _, &v)| v >= 10) {
-            break;
-        }
-
-        reset_flashed(&mut g);
-
-        // println!("\nstep {} end:", i);
-        // dump(&g);
-
-        step_count += 1;
-    }
+    let stats = octopuses.run_until(|stats, _| stats.fired == num_octopuses);
 
-    println!("\npart1: result = {}", step_count);
+    println!("\npart2: result = {}", stats.step);
 }
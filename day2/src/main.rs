@@ -1,82 +1,545 @@
+use std::env;
+use std::fmt;
+use std::fs;
 use std::io::prelude::*;
 
 type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
 
 fn main() -> Result<()> {
+    let options = Options::from_args()?;
     let input = read_input()?;
 
-    part1(&input);
-    part2(&input);
+    match options.model {
+        Some(model) => navigate(&input, model, model.name(), &options)?,
+        None => {
+            navigate(&input, &DirectModel, "part1", &options)?;
+            navigate(&input, &AimModel, "part2", &options)?;
+        }
+    }
 
     Ok(())
 }
 
-fn read_input() -> Result<Vec<Command>> {
+const USAGE: &str = "usage: day2 [--model <name>] [--csv <file>] [--svg <file>] < input";
+
+struct Options {
+    /// Runs just this model instead of both parts.
+    model: Option<&'static dyn NavigationModel>,
+    csv: Option<String>,
+    svg: Option<String>,
+}
+
+impl Options {
+    fn from_args() -> Result<Options> {
+        let mut options = Options {
+            model: None,
+            csv: None,
+            svg: None,
+        };
+        let mut args = env::args().skip(1);
+        while let Some(arg) = args.next() {
+            let value = args.next().ok_or(USAGE)?;
+            match arg.as_str() {
+                "--model" => options.model = Some(find_model(&value)?),
+                "--csv" => options.csv = Some(value),
+                "--svg" => options.svg = Some(value),
+                _ => return Err(USAGE.into()),
+            }
+        }
+        Ok(options)
+    }
+
+    /// The file to export `label`'s trajectory to. When both parts run, each
+    /// gets its own file with the label added before the extension.
+    fn export_path(&self, path: &str, label: &str) -> String {
+        if self.model.is_some() {
+            return path.to_string();
+        }
+        match path.rsplit_once('.') {
+            Some((stem, ext)) if !stem.is_empty() && !stem.ends_with('/') => {
+                format!("{}-{}.{}", stem, label, ext)
+            }
+            _ => format!("{}-{}", path, label),
+        }
+    }
+}
+
+fn read_input() -> Result<Vec<Line>> {
     let mut input = String::new();
     std::io::stdin().read_to_string(&mut input)?;
-    Ok(input
-        .lines()
-        .map(|line| parse_command(line).expect("invalid command"))
-        .collect())
-}
-
-fn parse_command(s: &str) -> Result<Command> {
-    let mut parts = s.split_whitespace();
-    let word = parts.next().unwrap();
-    let num = parts
-        .next()
-        .unwrap()
-        .parse::<u32>()
-        .expect("invalid number");
-    match word {
-        "forward" => Ok(Command::Forward(num)),
-        "down" => Ok(Command::Down(num)),
-        "up" => Ok(Command::Up(num)),
-        _ => Err("invalid command word".into()),
+    Ok(parse_script(&input)?)
+}
+
+struct ScriptError {
+    line: usize,
+    message: String,
+}
+
+impl ScriptError {
+    fn new(line: usize, message: impl Into<String>) -> ScriptError {
+        ScriptError {
+            line,
+            message: message.into(),
+        }
     }
 }
 
-#[derive(Debug)]
+impl fmt::Display for ScriptError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+// `main` reports errors with `Debug`, so make that read like `Display`.
+impl fmt::Debug for ScriptError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+
+impl std::error::Error for ScriptError {}
+
+#[derive(Debug, Clone, Copy)]
 enum Command {
-    Forward(u32),
-    Down(u32),
-    Up(u32),
+    Forward(i64),
+    Back(i64),
+    Down(i64),
+    Up(i64),
 }
 
-fn part1(input: &[Command]) {
-    let mut depth = 0;
-    let mut pos = 0;
+impl fmt::Display for Command {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Command::Forward(n) => write!(f, "forward {}", n),
+            Command::Back(n) => write!(f, "back {}", n),
+            Command::Down(n) => write!(f, "down {}", n),
+            Command::Up(n) => write!(f, "up {}", n),
+        }
+    }
+}
 
-    for command in input {
-        match &command {
-            Command::Forward(n) => pos += n,
-            Command::Down(n) => depth += n,
-            Command::Up(n) => depth -= n,
+impl Command {
+    /// The signed `(forward, down)` movement this command asks for.
+    fn deltas(self) -> Option<(i64, i64)> {
+        match self {
+            Command::Forward(n) => Some((n, 0)),
+            Command::Back(n) => Some((n.checked_neg()?, 0)),
+            Command::Down(n) => Some((0, n)),
+            Command::Up(n) => Some((0, n.checked_neg()?)),
         }
     }
+}
 
-    let result = depth * pos;
+#[derive(Debug)]
+enum Statement {
+    Move(Command),
+    Repeat(u32, Vec<Line>),
+    Checkpoint(String),
+}
 
-    println!("part1: {}", result);
+/// A statement and the 1-based script line it came from.
+#[derive(Debug)]
+struct Line {
+    num: usize,
+    statement: Statement,
 }
 
-fn part2(input: &[Command]) {
-    let mut aim = 0;
-    let mut depth = 0;
-    let mut pos = 0;
+/// Parses a script of one statement per line:
+///
+/// ```text
+/// # comments run to the end of the line
+/// forward 5
+/// back 2
+/// down -3
+/// repeat 4 {
+///     up 1
+/// }
+/// checkpoint surfaced
+/// ```
+fn parse_script(s: &str) -> std::result::Result<Vec<Line>, ScriptError> {
+    // Each open `repeat` block: its line, count and the statements so far.
+    let mut blocks: Vec<(usize, u32, Vec<Line>)> = Vec::new();
+    let mut top = Vec::new();
+
+    for (i, text) in s.lines().enumerate() {
+        let num = i + 1;
+        let text = text.split('#').next().unwrap().trim();
+        if text.is_empty() {
+            continue;
+        }
 
-    for command in input {
-        match &command {
-            Command::Forward(n) => {
-                pos += n;
-                depth += aim * n;
+        let words: Vec<&str> = text.split_whitespace().collect();
+        let statement = match words.as_slice() {
+            ["}"] => {
+                let (start, count, body) = blocks
+                    .pop()
+                    .ok_or_else(|| ScriptError::new(num, "unmatched '}'"))?;
+                Line {
+                    num: start,
+                    statement: Statement::Repeat(count, body),
+                }
             }
-            Command::Down(n) => aim += n,
-            Command::Up(n) => aim -= n,
+            ["repeat", count, "{"] => {
+                let count = count
+                    .parse()
+                    .map_err(|_| ScriptError::new(num, format!("invalid count: {}", count)))?;
+                blocks.push((num, count, Vec::new()));
+                continue;
+            }
+            ["repeat", ..] => return Err(ScriptError::new(num, "expected 'repeat <count> {'")),
+            ["checkpoint", name] => Line {
+                num,
+                statement: Statement::Checkpoint(name.to_string()),
+            },
+            ["checkpoint", ..] => {
+                return Err(ScriptError::new(num, "expected 'checkpoint <name>'"))
+            }
+            [word, amount] => Line {
+                num,
+                statement: Statement::Move(parse_command(word, amount, num)?),
+            },
+            _ => {
+                return Err(ScriptError::new(
+                    num,
+                    format!("invalid statement: {}", text),
+                ))
+            }
+        };
+
+        match blocks.last_mut() {
+            Some((_, _, body)) => body.push(statement),
+            None => top.push(statement),
         }
     }
 
-    let result = depth * pos;
+    if let Some((start, _, _)) = blocks.last() {
+        return Err(ScriptError::new(*start, "unclosed 'repeat' block"));
+    }
+
+    Ok(top)
+}
+
+fn parse_command(
+    word: &str,
+    amount: &str,
+    num: usize,
+) -> std::result::Result<Command, ScriptError> {
+    let n = amount
+        .parse::<i64>()
+        .map_err(|_| ScriptError::new(num, format!("invalid number: {}", amount)))?;
+    match word {
+        "forward" => Ok(Command::Forward(n)),
+        "back" => Ok(Command::Back(n)),
+        "down" => Ok(Command::Down(n)),
+        "up" => Ok(Command::Up(n)),
+        _ => Err(ScriptError::new(
+            num,
+            format!("invalid command word: {}", word),
+        )),
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct Submarine {
+    pos: i64,
+    depth: i64,
+    aim: i64,
+}
+
+impl fmt::Display for Submarine {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "pos = {}, depth = {}, aim = {}",
+            self.pos, self.depth, self.aim
+        )
+    }
+}
+
+/// The rules for how a command moves the submarine.
+trait NavigationModel {
+    fn name(&self) -> &'static str;
+
+    /// Applies `command`, returning `None` on arithmetic overflow.
+    fn apply(&self, sub: &mut Submarine, command: Command) -> Option<()>;
+}
+
+/// `down` and `up` change the depth directly (part 1).
+struct DirectModel;
+
+impl NavigationModel for DirectModel {
+    fn name(&self) -> &'static str {
+        "direct"
+    }
+
+    fn apply(&self, sub: &mut Submarine, command: Command) -> Option<()> {
+        let (forward, down) = command.deltas()?;
+        sub.pos = sub.pos.checked_add(forward)?;
+        sub.depth = sub.depth.checked_add(down)?;
+        Some(())
+    }
+}
+
+/// `down` and `up` steer the aim, and moving forward dives along it (part 2).
+struct AimModel;
+
+impl NavigationModel for AimModel {
+    fn name(&self) -> &'static str {
+        "aim"
+    }
+
+    fn apply(&self, sub: &mut Submarine, command: Command) -> Option<()> {
+        let (forward, down) = command.deltas()?;
+        sub.pos = sub.pos.checked_add(forward)?;
+        sub.aim = sub.aim.checked_add(down)?;
+        sub.depth = sub.depth.checked_add(sub.aim.checked_mul(forward)?)?;
+        Some(())
+    }
+}
+
+const MODELS: [&dyn NavigationModel; 2] = [&DirectModel, &AimModel];
+
+fn find_model(name: &str) -> Result<&'static dyn NavigationModel> {
+    MODELS
+        .iter()
+        .find(|m| m.name() == name)
+        .copied()
+        .ok_or_else(|| {
+            let names: Vec<&str> = MODELS.iter().map(|m| m.name()).collect();
+            format!(
+                "unknown model {:?}, expected one of: {}",
+                name,
+                names.join(", ")
+            )
+            .into()
+        })
+}
+
+/// The submarine's state after running the command on script line `line`.
+struct TrajectoryPoint {
+    line: usize,
+    command: Command,
+    sub: Submarine,
+}
+
+struct Interpreter<'m> {
+    sub: Submarine,
+    model: &'m dyn NavigationModel,
+    checkpoints: Vec<(String, Submarine)>,
+    /// Only recorded when asked for, since `repeat` can make it huge.
+    trajectory: Option<Vec<TrajectoryPoint>>,
+}
+
+impl<'m> Interpreter<'m> {
+    fn new(model: &'m dyn NavigationModel) -> Interpreter<'m> {
+        Interpreter {
+            sub: Submarine::default(),
+            model,
+            checkpoints: Vec::new(),
+            trajectory: None,
+        }
+    }
+
+    fn with_trajectory(mut self) -> Interpreter<'m> {
+        self.trajectory = Some(Vec::new());
+        self
+    }
+
+    fn run(&mut self, program: &[Line]) -> std::result::Result<(), ScriptError> {
+        for line in program {
+            match &line.statement {
+                Statement::Move(command) => {
+                    self.model
+                        .apply(&mut self.sub, *command)
+                        .ok_or_else(|| ScriptError::new(line.num, "arithmetic overflow"))?;
+                    if let Some(trajectory) = self.trajectory.as_mut() {
+                        trajectory.push(TrajectoryPoint {
+                            line: line.num,
+                            command: *command,
+                            sub: self.sub,
+                        });
+                    }
+                }
+                Statement::Repeat(count, body) => {
+                    for _ in 0..*count {
+                        self.run(body)?;
+                    }
+                }
+                Statement::Checkpoint(name) => {
+                    self.checkpoints.push((name.clone(), self.sub));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn print_checkpoints(&self, label: &str) {
+        for (name, sub) in self.checkpoints.iter() {
+            println!("{}: checkpoint {}: {}", label, name, sub);
+        }
+    }
+}
+
+fn navigate(
+    input: &[Line],
+    model: &dyn NavigationModel,
+    label: &str,
+    options: &Options,
+) -> Result<()> {
+    let mut interpreter = Interpreter::new(model);
+    if options.csv.is_some() || options.svg.is_some() {
+        interpreter = interpreter.with_trajectory();
+    }
+    interpreter.run(input)?;
+    interpreter.print_checkpoints(label);
+
+    let sub = interpreter.sub;
+    let result = sub.depth.checked_mul(sub.pos).ok_or("result overflows")?;
+
+    println!("{}: {}", label, result);
+
+    let trajectory = interpreter.trajectory.unwrap_or_default();
+    if let Some(path) = &options.csv {
+        let path = options.export_path(path, label);
+        write_file(&path, &trajectory_csv(&trajectory))?;
+        println!("{}: wrote trajectory to {}", label, path);
+    }
+    if let Some(path) = &options.svg {
+        let path = options.export_path(path, label);
+        write_file(
+            &path,
+            &trajectory_svg(&trajectory, &interpreter.checkpoints, label),
+        )?;
+        println!("{}: wrote depth profile to {}", label, path);
+    }
+
+    Ok(())
+}
+
+fn write_file(path: &str, contents: &str) -> Result<()> {
+    fs::write(path, contents).map_err(|e| format!("{}: {}", path, e).into())
+}
+
+fn trajectory_csv(trajectory: &[TrajectoryPoint]) -> String {
+    let mut csv = String::from("step,line,command,pos,depth,aim\n");
+    csv.push_str("0,,,0,0,0\n");
+    for (i, point) in trajectory.iter().enumerate() {
+        csv.push_str(&format!(
+            "{},{},{},{},{},{}\n",
+            i + 1,
+            point.line,
+            point.command,
+            point.sub.pos,
+            point.sub.depth,
+            point.sub.aim
+        ));
+    }
+    csv
+}
+
+/// Plots depth (increasing downwards) against horizontal position, starting
+/// from the surface at the origin, with checkpoints marked.
+fn trajectory_svg(
+    trajectory: &[TrajectoryPoint],
+    checkpoints: &[(String, Submarine)],
+    title: &str,
+) -> String {
+    const WIDTH: f64 = 800.0;
+    const HEIGHT: f64 = 400.0;
+    const MARGIN: f64 = 50.0;
+
+    let start = Submarine::default();
+    let subs: Vec<&Submarine> = std::iter::once(&start)
+        .chain(trajectory.iter().map(|p| &p.sub))
+        .collect();
+    let min_pos = subs.iter().map(|s| s.pos).min().unwrap();
+    let max_pos = subs.iter().map(|s| s.pos).max().unwrap();
+    let min_depth = subs.iter().map(|s| s.depth).min().unwrap();
+    let max_depth = subs.iter().map(|s| s.depth).max().unwrap();
+
+    // Spans between two i64s can exceed i64::MAX, so widen before subtracting.
+    let span = |min: i64, max: i64| (max as i128 - min as i128).max(1) as f64;
+    let x_scale = (WIDTH - 2.0 * MARGIN) / span(min_pos, max_pos);
+    let y_scale = (HEIGHT - 2.0 * MARGIN) / span(min_depth, max_depth);
+    let x = |pos: i64| MARGIN + (pos as i128 - min_pos as i128) as f64 * x_scale;
+    let y = |depth: i64| MARGIN + (depth as i128 - min_depth as i128) as f64 * y_scale;
+
+    let mut svg = String::new();
+    svg.push_str(&format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" viewBox=\"0 0 {w} {h}\">\n",
+        w = WIDTH,
+        h = HEIGHT
+    ));
+    svg.push_str(&format!(
+        "<rect width=\"{}\" height=\"{}\" fill=\"white\"/>\n",
+        WIDTH, HEIGHT
+    ));
+    svg.push_str(&format!(
+        "<text x=\"{}\" y=\"20\" font-family=\"sans-serif\" font-size=\"14\">{} depth profile</text>\n",
+        MARGIN, title
+    ));
+
+    if min_depth <= 0 && max_depth >= 0 {
+        svg.push_str(&format!(
+            "<line x1=\"{:.1}\" y1=\"{y:.1}\" x2=\"{:.1}\" y2=\"{y:.1}\" stroke=\"#7ab\" stroke-dasharray=\"4 4\"/>\n",
+            MARGIN,
+            WIDTH - MARGIN,
+            y = y(0)
+        ));
+    }
+
+    for (label, anchor, tx, ty) in [
+        (
+            format!("pos {}", min_pos),
+            "start",
+            MARGIN,
+            HEIGHT - MARGIN + 20.0,
+        ),
+        (
+            format!("pos {}", max_pos),
+            "end",
+            WIDTH - MARGIN,
+            HEIGHT - MARGIN + 20.0,
+        ),
+        (format!("depth {}", min_depth), "end", MARGIN - 5.0, MARGIN),
+        (
+            format!("depth {}", max_depth),
+            "end",
+            MARGIN - 5.0,
+            HEIGHT - MARGIN,
+        ),
+    ] {
+        svg.push_str(&format!(
+            "<text x=\"{:.1}\" y=\"{:.1}\" text-anchor=\"{}\" font-family=\"sans-serif\" font-size=\"10\">{}</text>\n",
+            tx, ty, anchor, label
+        ));
+    }
+
+    let points: Vec<String> = subs
+        .iter()
+        .map(|s| format!("{:.1},{:.1}", x(s.pos), y(s.depth)))
+        .collect();
+    svg.push_str(&format!(
+        "<polyline points=\"{}\" fill=\"none\" stroke=\"#036\" stroke-width=\"1.5\"/>\n",
+        points.join(" ")
+    ));
+
+    for (name, sub) in checkpoints {
+        svg.push_str(&format!(
+            "<circle cx=\"{:.1}\" cy=\"{:.1}\" r=\"4\" fill=\"#c30\"><title>{}: {}</title></circle>\n",
+            x(sub.pos),
+            y(sub.depth),
+            xml_escape(name),
+            sub
+        ));
+    }
+
+    svg.push_str("</svg>\n");
+    svg
+}
 
-    println!("part2: {}", result);
+fn xml_escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
 }
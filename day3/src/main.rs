@@ -1,123 +1,466 @@
+use std::env;
+use std::fmt::Write;
 use std::io::prelude::*;
 
 type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
 
 fn main() -> Result<()> {
+    let args: Vec<String> = env::args().skip(1).collect();
     let input = read_input()?;
 
-    part1(&input);
-    part2(&input);
+    match args
+        .iter()
+        .map(|s| s.as_str())
+        .collect::<Vec<_>>()
+        .as_slice()
+    {
+        [] => {
+            part1(&input)?;
+            part2(&input)?;
+        }
+        ["report"] => print!("{}", report_table(&input)),
+        ["report", "--json"] => println!("{}", report_json(&input)),
+        _ => return Err("usage: day3 [report [--json]] < input".into()),
+    }
 
     Ok(())
 }
 
-fn read_input() -> Result<Vec<Vec<u16>>> {
+/// The diagnostic readings, each packed into an integer whose lowest `width`
+/// bits hold the reading's digits, most significant first.
+struct Diagnostics {
+    width: usize,
+    readings: Vec<u128>,
+}
+
+fn read_input() -> Result<Diagnostics> {
     let mut input = String::new();
     std::io::stdin().read_to_string(&mut input)?;
-    Ok(input
-        .lines()
-        .map(|line| {
-            line.bytes()
-                .map(|b| (b - b'0') as u16)
-                .collect::<Vec<u16>>()
-        })
-        .collect())
+    parse_diagnostics(&input)
 }
 
-const NUM_BITS: usize = 12;
+fn parse_diagnostics(s: &str) -> Result<Diagnostics> {
+    let mut width = None;
+    let mut readings = Vec::new();
+
+    for (i, line) in s.lines().enumerate() {
+        let line = line.trim();
+        let bits = line
+            .bytes()
+            .map(|b| match b {
+                b'0' | b'1' => Ok(b - b'0'),
+                _ => Err(format!(
+                    "line {}: invalid binary digit {:?}",
+                    i + 1,
+                    b as char
+                )),
+            })
+            .collect::<std::result::Result<Vec<u8>, _>>()?;
+
+        if bits.is_empty() || bits.len() > u128::BITS as usize {
+            return Err(format!(
+                "line {}: readings must be 1 to {} bits wide",
+                i + 1,
+                u128::BITS
+            )
+            .into());
+        }
+
+        let expected = *width.get_or_insert(bits.len());
+        if bits.len() != expected {
+            return Err(format!(
+                "line {}: expected {} bits but found {}",
+                i + 1,
+                expected,
+                bits.len()
+            )
+            .into());
+        }
+
+        readings.push(bits_to_int(&bits));
+    }
+
+    let width = width.ok_or("no readings")?;
+
+    Ok(Diagnostics { width, readings })
+}
 
-fn part1(input: &[Vec<u16>]) {
-    let gamma = calc_gamma(&input);
-    let epsilon = !gamma & ((1 << NUM_BITS) - 1);
-    let result = gamma as u32 * epsilon as u32;
+fn part1(input: &Diagnostics) -> Result<()> {
+    let gamma = calc_gamma(&input.readings, input.width);
+    let epsilon = !gamma & mask(input.width);
+    let result = gamma.checked_mul(epsilon).ok_or("result overflows")?;
 
     println!("part1: {}", result);
+
+    Ok(())
 }
 
-fn part2(input: &[Vec<u16>]) {
-    let mut oxygen_input = input.to_owned();
+fn part2(input: &Diagnostics) -> Result<()> {
+    let trie = BitTrie::build(&input.readings, input.width);
 
-    for bit in 0..NUM_BITS {
-        if oxygen_input.len() == 1 {
-            break;
-        }
+    let oxygen = trie.find_rating(OXYGEN).ok_or("no oxygen rating")?;
+    debug_assert_eq!(
+        Some(oxygen),
+        find_rating_by_filter(&input.readings, input.width, OXYGEN)
+    );
+    println!("part2: oxygen = {}", oxygen);
 
-        let [count_0, count_1] = count_bits(&oxygen_input, bit);
-        let most_common = {
-            if count_1 > count_0 {
-                1
-            } else if count_0 > count_1 {
-                0
-            } else {
-                1
-            }
+    let co2 = trie.find_rating(CO2).ok_or("no CO2 rating")?;
+    debug_assert_eq!(
+        Some(co2),
+        find_rating_by_filter(&input.readings, input.width, CO2)
+    );
+    println!("part2: co2 = {}", co2);
+
+    let result = oxygen.checked_mul(co2).ok_or("result overflows")?;
+
+    println!("part2: {}", result);
+
+    Ok(())
+}
+
+#[derive(Clone, Copy)]
+enum Selection {
+    MostCommon,
+    LeastCommon,
+}
+
+/// How to pick which bit value survives at each position while searching
+/// for a rating.
+#[derive(Clone, Copy)]
+struct BitCriteria {
+    selection: Selection,
+    /// The bit value to keep when both are equally common.
+    tie_break: u8,
+}
+
+const OXYGEN: BitCriteria = BitCriteria {
+    selection: Selection::MostCommon,
+    tie_break: 1,
+};
+
+const CO2: BitCriteria = BitCriteria {
+    selection: Selection::LeastCommon,
+    tie_break: 0,
+};
+
+impl BitCriteria {
+    fn describe(&self) -> String {
+        let selection = match self.selection {
+            Selection::MostCommon => "most common",
+            Selection::LeastCommon => "least common",
         };
-        oxygen_input = oxygen_input
-            .into_iter()
-            .filter(|bits| bits[bit] == most_common)
-            .collect();
+        format!("{}, ties keep {}", selection, self.tie_break)
     }
 
-    let oxygen = bits_to_int(oxygen_input.first().unwrap());
-    println!("part2: oxygen = {}", oxygen);
+    fn choose(&self, count_0: usize, count_1: usize) -> u8 {
+        if count_0 == count_1 {
+            return self.tie_break;
+        }
+        match self.selection {
+            Selection::MostCommon => (count_1 > count_0) as u8,
+            Selection::LeastCommon => (count_1 < count_0) as u8,
+        }
+    }
+}
+
+/// Finds a rating by repeatedly filtering the readings, one bit position at a
+/// time, until only one is left. This is the straightforward version that
+/// `BitTrie::find_rating` is checked against.
+fn find_rating_by_filter(input: &[u128], width: usize, criteria: BitCriteria) -> Option<u128> {
+    trace_rating_by_filter(input, width, criteria).1
+}
+
+/// One round of filtering the candidates down to those with bit value `kept`
+/// at position `bit`.
+struct FilterStep {
+    bit: usize,
+    count_0: usize,
+    count_1: usize,
+    kept: u8,
+    remaining: usize,
+}
 
-    let mut co2_input = input.to_owned();
+fn trace_rating_by_filter(
+    input: &[u128],
+    width: usize,
+    criteria: BitCriteria,
+) -> (Vec<FilterStep>, Option<u128>) {
+    let mut remaining = input.to_owned();
+    let mut steps = Vec::new();
 
-    for bit in 0..NUM_BITS {
-        if co2_input.len() == 1 {
+    for bit in 0..width {
+        if remaining.len() == 1 {
             break;
         }
 
-        let [count_0, count_1] = count_bits(&co2_input, bit);
-        let least_common = {
-            if count_1 < count_0 {
-                1
-            } else if count_0 < count_1 {
-                0
-            } else {
-                0
-            }
+        let [count_0, count_1] = count_bits(&remaining, width, bit);
+        let keep = criteria.choose(count_0, count_1);
+        remaining.retain(|&reading| get_bit(reading, width, bit) == keep);
+        steps.push(FilterStep {
+            bit,
+            count_0,
+            count_1,
+            kept: keep,
+            remaining: remaining.len(),
+        });
+    }
+
+    (steps, remaining.first().copied())
+}
+
+#[derive(Clone, Copy, Default)]
+struct TrieNode {
+    children: [Option<usize>; 2],
+    /// Number of readings passing through this node.
+    count: usize,
+}
+
+/// A binary trie of the readings, most significant bit first, with each node
+/// counting the readings below it, so finding a rating takes one step per bit
+/// instead of a pass over the readings.
+struct BitTrie {
+    width: usize,
+    nodes: Vec<TrieNode>,
+}
+
+impl BitTrie {
+    fn build(readings: &[u128], width: usize) -> BitTrie {
+        let mut trie = BitTrie {
+            width,
+            nodes: vec![TrieNode::default()],
         };
-        co2_input = co2_input
-            .into_iter()
-            .filter(|bits| bits[bit] == least_common)
-            .collect();
+
+        for &reading in readings {
+            let mut node = 0;
+            trie.nodes[node].count += 1;
+            for pos in 0..width {
+                let bit = get_bit(reading, width, pos) as usize;
+                node = match trie.nodes[node].children[bit] {
+                    Some(child) => child,
+                    None => {
+                        trie.nodes.push(TrieNode::default());
+                        let child = trie.nodes.len() - 1;
+                        trie.nodes[node].children[bit] = Some(child);
+                        child
+                    }
+                };
+                trie.nodes[node].count += 1;
+            }
+        }
+
+        trie
     }
 
-    let co2 = bits_to_int(co2_input.first().unwrap());
-    println!("part2: co2 = {}", co2);
+    fn count(&self, node: Option<usize>) -> usize {
+        node.map_or(0, |n| self.nodes[n].count)
+    }
 
-    let result = oxygen as u32 * co2 as u32;
+    fn find_rating(&self, criteria: BitCriteria) -> Option<u128> {
+        let mut node = 0;
+        let mut rating = 0;
 
-    println!("part2: {}", result);
+        for _ in 0..self.width {
+            let TrieNode { children, count } = self.nodes[node];
+            let bit = if count == 1 {
+                // Only one reading left, so follow it to the end.
+                if children[0].is_some() {
+                    0
+                } else {
+                    1
+                }
+            } else {
+                criteria.choose(self.count(children[0]), self.count(children[1]))
+            };
+            node = children[bit as usize]?;
+            rating = (rating << 1) | bit as u128;
+        }
+
+        Some(rating)
+    }
 }
 
-fn calc_gamma(input: &[Vec<u16>]) -> u16 {
-    let mut gamma: [u16; NUM_BITS] = [0; NUM_BITS];
+/// The lowest `width` bits set.
+fn mask(width: usize) -> u128 {
+    u128::MAX >> (u128::BITS as usize - width)
+}
+
+/// The digit at `pos` of a `width`-bit reading, counting from the left.
+fn get_bit(reading: u128, width: usize, pos: usize) -> u8 {
+    ((reading >> (width - pos - 1)) & 1) as u8
+}
 
-    for bit in 0..NUM_BITS {
-        let [count_0, count_1] = count_bits(input, bit);
+fn calc_gamma(input: &[u128], width: usize) -> u128 {
+    let mut gamma = vec![0; width];
+
+    for (bit, g) in gamma.iter_mut().enumerate() {
+        let [count_0, count_1] = count_bits(input, width, bit);
         if count_1 > count_0 {
-            gamma[bit] = 1
+            *g = 1
         }
     }
 
     bits_to_int(&gamma)
 }
 
-fn count_bits(input: &[Vec<u16>], pos: usize) -> [u16; 2] {
-    let mut counts: [u16; 2] = [0; 2];
+fn count_bits(input: &[u128], width: usize, pos: usize) -> [usize; 2] {
+    let mut counts = [0; 2];
 
-    for num in input {
-        counts[num[pos] as usize] += 1;
+    for &num in input {
+        counts[get_bit(num, width, pos) as usize] += 1;
     }
 
     counts
 }
 
-fn bits_to_int(bits: &[u16]) -> u16 {
+fn bits_to_int(bits: &[u8]) -> u128 {
     let len = bits.len();
     bits.iter()
         .enumerate()
-        .fold(0, |acc, (i, bit)| acc | (bit << (len - i - 1)))
+        .fold(0, |acc, (i, &bit)| acc | ((bit as u128) << (len - i - 1)))
+}
+
+/// Which bit value is in the majority, or `None` for a tie.
+fn majority(count_0: usize, count_1: usize) -> Option<u8> {
+    match count_0.cmp(&count_1) {
+        std::cmp::Ordering::Greater => Some(0),
+        std::cmp::Ordering::Less => Some(1),
+        std::cmp::Ordering::Equal => None,
+    }
+}
+
+fn majority_name(count_0: usize, count_1: usize) -> String {
+    majority(count_0, count_1).map_or("tie".to_string(), |b| b.to_string())
+}
+
+/// How many more readings have a 1 than a 0.
+fn margin(count_0: usize, count_1: usize) -> i64 {
+    count_1 as i64 - count_0 as i64
+}
+
+const RATINGS: [(&str, BitCriteria); 2] = [("oxygen", OXYGEN), ("co2", CO2)];
+
+/// Bit positions count from the left, starting at 0.
+fn report_table(input: &Diagnostics) -> String {
+    let width = input.width;
+    let mut out = String::new();
+
+    let _ = writeln!(out, "{} readings, {} bits", input.readings.len(), width);
+    let _ = writeln!(out);
+    let _ = writeln!(
+        out,
+        "{:>4} {:>8} {:>8} {:>8} {:>8}",
+        "bit", "zeros", "ones", "margin", "majority"
+    );
+    for bit in 0..width {
+        let [count_0, count_1] = count_bits(&input.readings, width, bit);
+        let _ = writeln!(
+            out,
+            "{:>4} {:>8} {:>8} {:>+8} {:>8}",
+            bit,
+            count_0,
+            count_1,
+            margin(count_0, count_1),
+            majority_name(count_0, count_1)
+        );
+    }
+
+    for (name, criteria) in RATINGS {
+        let (steps, rating) = trace_rating_by_filter(&input.readings, width, criteria);
+        let _ = writeln!(out);
+        let _ = writeln!(out, "{} rating ({}):", name, criteria.describe());
+        let _ = writeln!(
+            out,
+            "{:>4} {:>8} {:>8} {:>8} {:>8} {:>10}",
+            "bit", "zeros", "ones", "margin", "kept", "remaining"
+        );
+        let _ = writeln!(
+            out,
+            "{:>4} {:>8} {:>8} {:>8} {:>8} {:>10}",
+            "-",
+            "",
+            "",
+            "",
+            "",
+            input.readings.len()
+        );
+        for step in steps.iter() {
+            let _ = writeln!(
+                out,
+                "{:>4} {:>8} {:>8} {:>+8} {:>8} {:>10}",
+                step.bit,
+                step.count_0,
+                step.count_1,
+                margin(step.count_0, step.count_1),
+                step.kept,
+                step.remaining
+            );
+        }
+        match rating {
+            Some(rating) => {
+                let _ = writeln!(out, "{} = {:0w$b} = {}", name, rating, rating, w = width);
+            }
+            None => {
+                let _ = writeln!(out, "{} = none", name);
+            }
+        }
+    }
+
+    out
+}
+
+fn report_json(input: &Diagnostics) -> String {
+    let width = input.width;
+
+    let bits: Vec<String> = (0..width)
+        .map(|bit| {
+            let [count_0, count_1] = count_bits(&input.readings, width, bit);
+            format!(
+                r#"{{"bit":{},"zeros":{},"ones":{},"margin":{},"majority":"{}"}}"#,
+                bit,
+                count_0,
+                count_1,
+                margin(count_0, count_1),
+                majority_name(count_0, count_1)
+            )
+        })
+        .collect();
+
+    let ratings: Vec<String> = RATINGS
+        .iter()
+        .map(|&(name, criteria)| {
+            let (steps, rating) = trace_rating_by_filter(&input.readings, width, criteria);
+            let steps: Vec<String> = steps
+                .iter()
+                .map(|step| {
+                    format!(
+                        r#"{{"bit":{},"zeros":{},"ones":{},"margin":{},"kept":{},"remaining":{}}}"#,
+                        step.bit,
+                        step.count_0,
+                        step.count_1,
+                        margin(step.count_0, step.count_1),
+                        step.kept,
+                        step.remaining
+                    )
+                })
+                .collect();
+            // Ratings can exceed what JSON numbers hold exactly, so use strings.
+            let rating = rating.map_or("null".to_string(), |r| format!("\"{}\"", r));
+            format!(
+                r#""{}":{{"criteria":"{}","rating":{},"steps":[{}]}}"#,
+                name,
+                criteria.describe(),
+                rating,
+                steps.join(",")
+            )
+        })
+        .collect();
+
+    format!(
+        r#"{{"readings":{},"width":{},"bits":[{}],"ratings":{{{}}}}}"#,
+        input.readings.len(),
+        width,
+        bits.join(","),
+        ratings.join(",")
+    )
 }
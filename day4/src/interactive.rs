@@ -0,0 +1,193 @@
+use crate::{AdvanceResult, Bingo, Board, Result, Win, WinLine};
+use std::io::prelude::*;
+
+const HELP: &str =
+    "keys: <enter>/n = next draw, w = run to next winner, j <turn> = jump to turn, q = quit";
+
+/// Lets the user watch a game draw by draw, reading commands from `keys`.
+pub fn step_through(initial: &Bingo, keys: impl BufRead) -> Result<()> {
+    let mut session = Session::new(initial);
+
+    println!("{}", HELP);
+    session.render();
+    prompt()?;
+
+    for line in keys.lines() {
+        let line = line?;
+        let words: Vec<&str> = line.split_whitespace().collect();
+        match words.as_slice() {
+            [] | ["n"] => {
+                if !session.step() {
+                    println!("no more numbers");
+                }
+            }
+            ["w"] => {
+                if !session.run_to_next_winner() {
+                    println!("no more winners");
+                }
+            }
+            ["j", turn] => match turn.parse() {
+                Ok(turn) => {
+                    if !session.jump_to(turn) {
+                        println!("the game ends after turn {}", session.bingo.turn);
+                    }
+                }
+                Err(_) => println!("invalid turn: {}", turn),
+            },
+            ["q"] => break,
+            _ => {
+                println!("{}", HELP);
+                prompt()?;
+                continue;
+            }
+        }
+        session.render();
+        prompt()?;
+    }
+
+    Ok(())
+}
+
+fn prompt() -> Result<()> {
+    print!("> ");
+    std::io::stdout().flush()?;
+    Ok(())
+}
+
+struct Session<'a> {
+    initial: &'a Bingo,
+    bingo: Bingo,
+    last_num: Option<u32>,
+    /// How each board won, if it has.
+    wins: Vec<Option<Win>>,
+}
+
+impl<'a> Session<'a> {
+    fn new(initial: &'a Bingo) -> Session<'a> {
+        Session {
+            initial,
+            bingo: initial.clone(),
+            last_num: None,
+            wins: vec![None; initial.boards.len()],
+        }
+    }
+
+    /// Draws one number, returning the boards that won on it, or `None` if
+    /// there were no numbers left.
+    fn advance(&mut self) -> Option<Vec<Win>> {
+        let num = self.bingo.numbers.front().copied();
+        let winners = match self.bingo.advance() {
+            AdvanceResult::Winners(winners) => winners,
+            AdvanceResult::NoWinner => Vec::new(),
+            AdvanceResult::NoMoreNumbers => return None,
+        };
+        self.last_num = num;
+        for win in winners.iter() {
+            self.wins[win.board] = Some(*win);
+        }
+        Some(winners)
+    }
+
+    fn step(&mut self) -> bool {
+        self.advance().is_some()
+    }
+
+    fn run_to_next_winner(&mut self) -> bool {
+        while let Some(winners) = self.advance() {
+            if !winners.is_empty() {
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Moves to just after draw number `turn`, replaying from the start if
+    /// that's in the past.
+    fn jump_to(&mut self, turn: usize) -> bool {
+        if turn < self.bingo.turn {
+            *self = Session::new(self.initial);
+        }
+        while self.bingo.turn < turn {
+            if !self.step() {
+                return false;
+            }
+        }
+        true
+    }
+
+    fn render(&self) {
+        println!();
+        match self.last_num {
+            Some(num) => println!("turn {}: drew {}", self.bingo.turn, num),
+            None => println!("turn 0: nothing drawn yet"),
+        }
+
+        for (b, board) in self.bingo.boards.iter().enumerate() {
+            println!();
+            match &self.wins[b] {
+                Some(win) => println!(
+                    "board {}: won on turn {} ({} with {}), score {}",
+                    b + 1,
+                    win.turn,
+                    win.line,
+                    win.num,
+                    win.score
+                ),
+                None => println!("board {}:", b + 1),
+            }
+
+            if self.wins[b].is_some() {
+                println!("{}", board);
+                continue;
+            }
+
+            let close: Vec<(WinLine, usize)> = board
+                .lines_to_go(&self.bingo.rules)
+                .into_iter()
+                .filter(|&(_, to_go)| to_go <= 1)
+                .collect();
+            println!("{}", render_board(board, &close));
+            if !close.is_empty() {
+                let close: Vec<String> = close
+                    .iter()
+                    .map(|(line, to_go)| format!("{} ({} to go)", line, to_go))
+                    .collect();
+                println!("close: {}", close.join(", "));
+            }
+        }
+
+        if let Some(next) = self.bingo.numbers.front() {
+            println!("\nnext number: {}", next);
+        }
+    }
+}
+
+/// Draws `board` like its `Display`, with a `<` after each row and a `^`
+/// under each column in `close`.
+fn render_board(board: &Board, close: &[(WinLine, usize)]) -> String {
+    let is_close = |line: WinLine| close.iter().any(|&(l, _)| l == line);
+
+    let mut out = String::new();
+    for (x, y, slot) in board.grid.iter() {
+        if x == 0 && y > 0 {
+            out.push('\n');
+        }
+        out.push_str(&format!("{} ", slot));
+        if x == board.size() - 1 && is_close(WinLine::Row(y)) {
+            out.push('<');
+        }
+    }
+
+    if (0..board.size()).any(|x| is_close(WinLine::Column(x))) {
+        out.push('\n');
+        for x in 0..board.size() {
+            out.push_str(if is_close(WinLine::Column(x)) {
+                " ^  "
+            } else {
+                "    "
+            });
+        }
+    }
+
+    out
+}
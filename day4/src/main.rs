@@ -1,23 +1,109 @@
+mod interactive;
+mod simulate;
+
+use interactive::step_through;
 use shared::Grid;
+use simulate::{print_simulation, simulate};
 use std::collections::vec_deque::*;
+use std::collections::HashMap;
+use std::env;
 use std::fmt;
+use std::fs::File;
 use std::io::prelude::*;
+use std::io::BufReader;
 
 type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
 
 fn main() -> Result<()> {
-    let input = read_input()?;
-
-    part1(input.clone());
-    println!();
-    part2(input);
+    let options = Options::from_args()?;
+    let mut bingo = match &options.mode {
+        // The keys come in on stdin, so the puzzle has to come from a file.
+        Mode::Step { path } => read_input(BufReader::new(File::open(path)?), options.rules)?,
+        _ => read_input(std::io::stdin(), options.rules)?,
+    };
+
+    match options.mode {
+        Mode::Answers => {
+            let timeline = bingo.timeline();
+            part1(&bingo, &timeline)?;
+            println!();
+            part2(&bingo, &timeline)?;
+        }
+        Mode::Timeline => print_timeline(&bingo.timeline()),
+        Mode::Simulate { games, seed } => {
+            print_simulation(&simulate(&bingo, games, seed), seed);
+        }
+        Mode::Step { .. } => step_through(&bingo, std::io::stdin().lock())?,
+    }
 
     Ok(())
 }
 
-fn read_input() -> Result<Bingo> {
+const USAGE: &str =
+    "usage: day4 [timeline | simulate [--games <n>] [--seed <n>]] [--rules <rule>,...] < input
+       day4 step <input> [--rules <rule>,...]
+rules: rows, columns, diagonals, corners, full (default: rows,columns)";
+
+enum Mode {
+    /// Print the two puzzle answers.
+    Answers,
+    /// Print every board's result.
+    Timeline,
+    /// Estimate each board's chance of winning first over random draw orders.
+    Simulate { games: usize, seed: u64 },
+    /// Walk through the game a draw at a time, driven from the keyboard.
+    Step { path: String },
+}
+
+struct Options {
+    mode: Mode,
+    rules: Vec<WinRule>,
+}
+
+impl Options {
+    fn from_args() -> Result<Options> {
+        let mut options = Options {
+            mode: Mode::Answers,
+            rules: WinRule::STANDARD.to_vec(),
+        };
+        let mut args = env::args().skip(1);
+        while let Some(arg) = args.next() {
+            match (arg.as_str(), &mut options.mode) {
+                ("timeline", Mode::Answers) => options.mode = Mode::Timeline,
+                ("simulate", Mode::Answers) => {
+                    options.mode = Mode::Simulate {
+                        games: 10_000,
+                        seed: 2021,
+                    }
+                }
+                ("step", Mode::Answers) => {
+                    options.mode = Mode::Step {
+                        path: args.next().ok_or(USAGE)?,
+                    }
+                }
+                ("--games", Mode::Simulate { games, .. }) => {
+                    *games = args.next().ok_or(USAGE)?.parse()?;
+                }
+                ("--seed", Mode::Simulate { seed, .. }) => {
+                    *seed = args.next().ok_or(USAGE)?.parse()?;
+                }
+                ("--rules", _) => {
+                    let rules = args.next().ok_or(USAGE)?;
+                    options.rules = rules
+                        .split(',')
+                        .map(WinRule::parse)
+                        .collect::<Result<_>>()?;
+                }
+                _ => return Err(USAGE.into()),
+            }
+        }
+        Ok(options)
+    }
+}
+
+fn read_input(mut reader: impl Read, rules: Vec<WinRule>) -> Result<Bingo> {
     let mut input = String::new();
-    std::io::stdin().read_to_string(&mut input)?;
+    reader.read_to_string(&mut input)?;
 
     let mut lines = input.lines();
     let numbers_line = lines.next().unwrap();
@@ -31,28 +117,28 @@ fn read_input() -> Result<Bingo> {
     let mut boards: Vec<Board> = Vec::new();
     let mut rows = Vec::new();
 
-    while let Some(line) = lines.next() {
-        if line.len() == 0 {
-            boards.push(Board::new(rows));
-            rows = Vec::new();
+    for line in lines {
+        if line.is_empty() {
+            // Runs of blank lines don't make empty boards.
+            if !rows.is_empty() {
+                boards.push(Board::new(rows)?);
+                rows = Vec::new();
+            }
         } else {
             let row: Vec<BoardSlot> = line
                 .split_whitespace()
                 .map(|s| s.parse().unwrap())
-                .map(|num| BoardSlot::new(num))
+                .map(BoardSlot::new)
                 .collect();
             rows.push(row);
         }
     }
 
     if !rows.is_empty() {
-        boards.push(Board::new(rows));
+        boards.push(Board::new(rows)?);
     }
 
-    Ok(Bingo {
-        boards,
-        numbers: VecDeque::from(numbers),
-    })
+    Ok(Bingo::new(boards, numbers, rules))
 }
 
 #[derive(Clone, Default)]
@@ -78,20 +164,113 @@ impl fmt::Display for BoardSlot {
     }
 }
 
+/// Running totals of marked slots, so checking for a win never has to scan
+/// the board.
+#[derive(Clone)]
+struct MarkCounts {
+    rows: Vec<usize>,
+    cols: Vec<usize>,
+    diagonal: usize,
+    anti_diagonal: usize,
+    corners: usize,
+    total: usize,
+}
+
+impl MarkCounts {
+    fn new(size: usize) -> MarkCounts {
+        MarkCounts {
+            rows: vec![0; size],
+            cols: vec![0; size],
+            diagonal: 0,
+            anti_diagonal: 0,
+            corners: 0,
+            total: 0,
+        }
+    }
+}
+
 #[derive(Clone)]
-struct Board(Grid<BoardSlot>);
+struct Board {
+    grid: Grid<BoardSlot>,
+    counts: MarkCounts,
+}
 
 impl Board {
-    pub fn new(rows: Vec<Vec<BoardSlot>>) -> Board {
-        Self(Grid::new_square_with_rows(5, rows))
+    pub fn new(rows: Vec<Vec<BoardSlot>>) -> Result<Board> {
+        let size = rows.len();
+        if let Some(row) = rows.iter().find(|row| row.len() != size) {
+            return Err(format!(
+                "board with {} rows has a row of {} numbers; boards must be square",
+                size,
+                row.len()
+            )
+            .into());
+        }
+        Ok(Board {
+            grid: Grid::new_square_with_rows(size, rows),
+            counts: MarkCounts::new(size),
+        })
+    }
+
+    fn size(&self) -> usize {
+        self.grid.width()
+    }
+
+    fn is_corner(&self, x: usize, y: usize) -> bool {
+        let last = self.size() - 1;
+        (x == 0 || x == last) && (y == 0 || y == last)
+    }
+
+    /// How many more marks each line that counts under `rules` needs.
+    fn lines_to_go(&self, rules: &[WinRule]) -> Vec<(WinLine, usize)> {
+        let size = self.size();
+        let counts = &self.counts;
+        let mut lines = Vec::new();
+
+        for rule in rules {
+            match rule {
+                WinRule::Rows => lines.extend(
+                    counts
+                        .rows
+                        .iter()
+                        .enumerate()
+                        .map(|(y, c)| (WinLine::Row(y), size - c)),
+                ),
+                WinRule::Columns => lines.extend(
+                    counts
+                        .cols
+                        .iter()
+                        .enumerate()
+                        .map(|(x, c)| (WinLine::Column(x), size - c)),
+                ),
+                WinRule::Diagonals => {
+                    lines.push((WinLine::Diagonal, size - counts.diagonal));
+                    lines.push((WinLine::AntiDiagonal, size - counts.anti_diagonal));
+                }
+                WinRule::FourCorners => {
+                    lines.push((WinLine::FourCorners, self.num_corners() - counts.corners))
+                }
+                WinRule::FullCard => lines.push((WinLine::FullCard, size * size - counts.total)),
+            }
+        }
+        lines
+    }
+
+    /// A 1x1 board's four corners are all the same slot.
+    fn num_corners(&self) -> usize {
+        if self.size() == 1 {
+            1
+        } else {
+            4
+        }
     }
 }
 
 impl fmt::Display for Board {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        for (x, y, v) in self.0.iter() {
+        for (x, y, v) in self.grid.iter() {
             if x == 0 && y > 0 {
-                write!(f, "\n")?;
+                writeln!(f)?;
             }
             write!(f, "{} ", v)?;
         }
@@ -100,41 +279,157 @@ impl fmt::Display for Board {
     }
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum WinRule {
+    /// Any complete row.
+    Rows,
+    /// Any complete column.
+    Columns,
+    /// Either complete diagonal.
+    Diagonals,
+    /// All four corners.
+    FourCorners,
+    /// Every number on the board.
+    FullCard,
+}
+
+impl WinRule {
+    const STANDARD: [WinRule; 2] = [WinRule::Rows, WinRule::Columns];
+
+    fn parse(s: &str) -> Result<WinRule> {
+        match s.trim() {
+            "rows" => Ok(WinRule::Rows),
+            "columns" => Ok(WinRule::Columns),
+            "diagonals" => Ok(WinRule::Diagonals),
+            "corners" => Ok(WinRule::FourCorners),
+            "full" => Ok(WinRule::FullCard),
+            _ => Err(format!("unknown win rule: {}\n{}", s, USAGE).into()),
+        }
+    }
+}
+
+/// The marked line that made a board win.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum WinLine {
+    Row(usize),
+    Column(usize),
+    /// Top-left to bottom-right.
+    Diagonal,
+    /// Top-right to bottom-left.
+    AntiDiagonal,
+    FourCorners,
+    FullCard,
+}
+
+impl fmt::Display for WinLine {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WinLine::Row(y) => write!(f, "row {}", y + 1),
+            WinLine::Column(x) => write!(f, "column {}", x + 1),
+            WinLine::Diagonal => write!(f, "diagonal"),
+            WinLine::AntiDiagonal => write!(f, "anti-diagonal"),
+            WinLine::FourCorners => write!(f, "four corners"),
+            WinLine::FullCard => write!(f, "full card"),
+        }
+    }
+}
+
 trait BoardMarking {
-    fn mark_num(&mut self, num: u32);
-    fn is_winner(&self) -> bool;
+    /// Marks the slot at `(x, y)`, returning whether it was unmarked before.
+    fn mark_at(&mut self, x: usize, y: usize) -> bool;
+    /// The line through `(x, y)` that is complete under `rules`, if any.
+    fn completed_line_through(&self, x: usize, y: usize, rules: &[WinRule]) -> Option<WinLine>;
+    /// The first line completed under any of `rules`, if the board has won.
+    fn winning_line(&self, rules: &[WinRule]) -> Option<WinLine>;
     fn sum_unmarked(&self) -> u32;
+
+    fn is_winner(&self, rules: &[WinRule]) -> bool {
+        self.winning_line(rules).is_some()
+    }
 }
 
 impl BoardMarking for Board {
-    fn mark_num(&mut self, num: u32) {
-        for y in 0..5 {
-            for x in 0..5 {
-                if self.0[(x, y)].num == num {
-                    self.0[(x, y)].marked = true;
-                }
-            }
+    fn mark_at(&mut self, x: usize, y: usize) -> bool {
+        if self.grid[(x, y)].marked {
+            return false;
+        }
+        self.grid[(x, y)].marked = true;
+
+        let last = self.size() - 1;
+        let is_corner = self.is_corner(x, y);
+        let counts = &mut self.counts;
+        counts.rows[y] += 1;
+        counts.cols[x] += 1;
+        if x == y {
+            counts.diagonal += 1;
+        }
+        if x + y == last {
+            counts.anti_diagonal += 1;
+        }
+        if is_corner {
+            counts.corners += 1;
         }
+        counts.total += 1;
+        true
     }
 
-    fn is_winner(&self) -> bool {
-        for y in 0..5 {
-            if self.0.get_row(y).iter().all(|s| s.marked) {
-                return true;
+    // Only looks at the lines through `(x, y)`, so checking whether marking
+    // it just won the board takes constant time.
+    fn completed_line_through(&self, x: usize, y: usize, rules: &[WinRule]) -> Option<WinLine> {
+        let size = self.size();
+        let counts = &self.counts;
+
+        rules.iter().find_map(|rule| match rule {
+            WinRule::Rows => (counts.rows[y] == size).then_some(WinLine::Row(y)),
+            WinRule::Columns => (counts.cols[x] == size).then_some(WinLine::Column(x)),
+            WinRule::Diagonals => {
+                if x == y && counts.diagonal == size {
+                    Some(WinLine::Diagonal)
+                } else if x + y == size - 1 && counts.anti_diagonal == size {
+                    Some(WinLine::AntiDiagonal)
+                } else {
+                    None
+                }
             }
-        }
+            WinRule::FourCorners => (self.is_corner(x, y) && counts.corners == self.num_corners())
+                .then_some(WinLine::FourCorners),
+            WinRule::FullCard => (counts.total == size * size).then_some(WinLine::FullCard),
+        })
+    }
 
-        for x in 0..5 {
-            if self.0.get_col(x).iter().all(|s| s.marked) {
-                return true;
-            }
-        }
+    fn winning_line(&self, rules: &[WinRule]) -> Option<WinLine> {
+        let size = self.size();
+        let counts = &self.counts;
 
-        false
+        rules.iter().find_map(|rule| match rule {
+            WinRule::Rows => counts
+                .rows
+                .iter()
+                .position(|&c| c == size)
+                .map(WinLine::Row),
+            WinRule::Columns => counts
+                .cols
+                .iter()
+                .position(|&c| c == size)
+                .map(WinLine::Column),
+            WinRule::Diagonals => {
+                if counts.diagonal == size {
+                    Some(WinLine::Diagonal)
+                } else if counts.anti_diagonal == size {
+                    Some(WinLine::AntiDiagonal)
+                } else {
+                    None
+                }
+            }
+            WinRule::FourCorners => {
+                (counts.corners == self.num_corners()).then_some(WinLine::FourCorners)
+            }
+            WinRule::FullCard => (counts.total == size * size).then_some(WinLine::FullCard),
+        })
     }
 
     fn sum_unmarked(&self) -> u32 {
-        self.0
+        self.grid
             .cells
             .iter()
             .filter(|s| !s.marked)
@@ -143,26 +438,82 @@ impl BoardMarking for Board {
     }
 }
 
+/// Where a number appears: `(board, x, y)`.
+type SlotRef = (usize, usize, usize);
+
 #[derive(Clone)]
 struct Bingo {
     boards: Vec<Board>,
     numbers: VecDeque<u32>,
+    rules: Vec<WinRule>,
+    /// Every slot holding each number.
+    index: HashMap<u32, Vec<SlotRef>>,
+    /// Boards that have already won take no further part in the game.
+    won: Vec<bool>,
+    /// Number of draws so far.
+    turn: usize,
+}
+
+/// A board winning: on which draw (`turn`, counting from 1), with which
+/// number and line, and its score at that moment.
+#[derive(Clone, Copy, Debug)]
+struct Win {
+    board: usize,
+    turn: usize,
+    num: u32,
+    line: WinLine,
+    score: u32,
+}
+
+/// Every board's result over a whole game. Boards that win on the same draw
+/// appear in board order.
+struct Timeline {
+    wins: Vec<Win>,
+    never_won: Vec<usize>,
 }
 
 enum AdvanceResult {
-    Winner(Board, u32),
+    Winners(Vec<Win>),
     NoWinner,
     NoMoreNumbers,
 }
 
 impl Bingo {
+    fn new(boards: Vec<Board>, numbers: Vec<u32>, rules: Vec<WinRule>) -> Bingo {
+        let mut index: HashMap<u32, Vec<SlotRef>> = HashMap::new();
+        for (b, board) in boards.iter().enumerate() {
+            for (x, y, slot) in board.grid.iter() {
+                index.entry(slot.num).or_default().push((b, x, y));
+            }
+        }
+
+        Bingo {
+            won: vec![false; boards.len()],
+            boards,
+            numbers: VecDeque::from(numbers),
+            rules,
+            index,
+            turn: 0,
+        }
+    }
+
     fn advance(&mut self) -> AdvanceResult {
         if let Some(num) = self.numbers.pop_front() {
-            self.mark_boards(num);
-
-            let winners = self.remove_winners();
-            if let Some(winner) = winners.into_iter().nth(0) {
-                return AdvanceResult::Winner(winner, num);
+            self.turn += 1;
+            let winners = self.mark_boards(num);
+
+            if !winners.is_empty() {
+                let wins = winners
+                    .into_iter()
+                    .map(|(board, line)| Win {
+                        board,
+                        turn: self.turn,
+                        num,
+                        line,
+                        score: self.boards[board].sum_unmarked() * num,
+                    })
+                    .collect();
+                return AdvanceResult::Winners(wins);
             }
 
             AdvanceResult::NoWinner
@@ -171,76 +522,99 @@ impl Bingo {
         }
     }
 
-    fn mark_boards(&mut self, num: u32) {
-        for board in self.boards.iter_mut() {
-            board.mark_num(num);
-        }
-    }
-
-    fn remove_winners(&mut self) -> Vec<Board> {
+    /// Marks `num` on every board that has it, returning the boards that won
+    /// because of it, in board order, with their winning lines.
+    fn mark_boards(&mut self, num: u32) -> Vec<(usize, WinLine)> {
         let mut winners = Vec::new();
 
-        while self.boards.len() > 0 {
-            let winner = self
-                .boards
-                .iter()
-                .enumerate()
-                .find(|(_, b)| b.is_winner())
-                .map(|(i, _)| i);
-
-            if let Some(winner) = winner.map(|i| self.boards.remove(i)) {
-                winners.push(winner);
-            } else {
-                break;
+        for &(b, x, y) in self.index.get(&num).into_iter().flatten() {
+            if self.won[b] {
+                continue;
+            }
+            let board = &mut self.boards[b];
+            if !board.mark_at(x, y) {
+                continue;
+            }
+            if let Some(line) = board.completed_line_through(x, y, &self.rules) {
+                debug_assert!(board.is_winner(&self.rules));
+                self.won[b] = true;
+                winners.push((b, line));
             }
         }
 
+        winners.sort_unstable_by_key(|&(b, _)| b);
         winners
     }
 
-    fn find_first_winning_board(&mut self) -> (Board, u32) {
+    /// Plays every remaining number. Boards stop being marked once they win,
+    /// so afterwards each board is left as it was when it won.
+    fn timeline(&mut self) -> Timeline {
+        let mut wins = Vec::new();
+
         loop {
             match self.advance() {
-                AdvanceResult::Winner(b, num) => return (b, num),
+                AdvanceResult::Winners(mut w) => wins.append(&mut w),
                 AdvanceResult::NoWinner => {}
-                AdvanceResult::NoMoreNumbers => panic!("no winning board!"),
+                AdvanceResult::NoMoreNumbers => break,
             }
         }
-    }
 
-    fn find_last_winning_board(&mut self) -> (Board, u32) {
-        let mut last_winner = None;
+        let never_won = (0..self.boards.len()).filter(|&b| !self.won[b]).collect();
 
-        loop {
-            match self.advance() {
-                AdvanceResult::Winner(b, num) => {
-                    last_winner = Some((b, num));
-                }
-                AdvanceResult::NoWinner => {}
-                AdvanceResult::NoMoreNumbers => return last_winner.expect("no winning board!"),
-            }
-        }
+        Timeline { wins, never_won }
     }
 }
 
-fn part1(mut bingo: Bingo) {
-    let (board, winning_num) = bingo.find_first_winning_board();
-
-    println!("part1: winning num = {}", winning_num);
-    println!("part1: winning board:\n{}", board);
-
-    let result = board.sum_unmarked() * winning_num;
+fn print_timeline(timeline: &Timeline) {
+    println!(
+        "{:>5} {:>5} {:>5} {:>6}  {:<14} {:>8}",
+        "rank", "turn", "num", "board", "line", "score"
+    );
+    let mut rank = 0;
+    let mut last_turn = 0;
+    for (i, win) in timeline.wins.iter().enumerate() {
+        // Boards winning on the same draw share a rank.
+        if win.turn != last_turn {
+            rank = i + 1;
+            last_turn = win.turn;
+        }
+        println!(
+            "{:>5} {:>5} {:>5} {:>6}  {:<14} {:>8}",
+            rank,
+            win.turn,
+            win.num,
+            win.board + 1,
+            win.line.to_string(),
+            win.score
+        );
+    }
 
-    println!("part1: result = {}", result);
+    if !timeline.never_won.is_empty() {
+        let boards: Vec<String> = timeline
+            .never_won
+            .iter()
+            .map(|b| (b + 1).to_string())
+            .collect();
+        println!("never won: boards {}", boards.join(", "));
+    }
 }
 
-fn part2(mut bingo: Bingo) {
-    let (board, num) = bingo.find_last_winning_board();
-
-    println!("part2: winning num = {}", num);
-    println!("part2: winning board:\n{}", board);
+fn print_win(label: &str, bingo: &Bingo, win: &Win) {
+    println!("{}: winning num = {}", label, win.num);
+    println!("{}: winning turn = {}", label, win.turn);
+    println!("{}: winning line = {}", label, win.line);
+    println!("{}: winning board:\n{}", label, bingo.boards[win.board]);
+    println!("{}: result = {}", label, win.score);
+}
 
-    let result = board.sum_unmarked() * num;
+fn part1(bingo: &Bingo, timeline: &Timeline) -> Result<()> {
+    let win = timeline.wins.first().ok_or("no winning board!")?;
+    print_win("part1", bingo, win);
+    Ok(())
+}
 
-    println!("part2: result = {}", result);
+fn part2(bingo: &Bingo, timeline: &Timeline) -> Result<()> {
+    let win = timeline.wins.last().ok_or("no winning board!")?;
+    print_win("part2", bingo, win);
+    Ok(())
 }
@@ -0,0 +1,135 @@
+use crate::{AdvanceResult, Bingo};
+use std::collections::VecDeque;
+
+/// SplitMix64: tiny, fast and good enough to shuffle draw orders, and fully
+/// determined by its seed so runs can be reproduced.
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Rng {
+        Rng(seed)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// A uniformly distributed number in `0..n`.
+    pub fn below(&mut self, n: u64) -> u64 {
+        // Reject the top sliver of values that would bias the modulo.
+        let zone = u64::MAX - u64::MAX % n;
+        loop {
+            let v = self.next_u64();
+            if v < zone {
+                return v % n;
+            }
+        }
+    }
+
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            let j = self.below(i as u64 + 1) as usize;
+            items.swap(i, j);
+        }
+    }
+}
+
+pub struct Estimate {
+    pub board: usize,
+    /// Games won outright count 1; a first place shared by `k` boards
+    /// counts `1/k` to each.
+    pub wins: f64,
+    pub probability: f64,
+    /// Half-width of the 95% confidence interval around `probability`.
+    pub margin: f64,
+}
+
+pub struct Simulation {
+    pub games: usize,
+    /// Games in which no board won at all.
+    pub no_winner: usize,
+    /// Every board, most likely winner first.
+    pub estimates: Vec<Estimate>,
+}
+
+/// Plays `games` games with the boards and rules of `bingo`, each drawing
+/// every number in the range of the original draws in a fresh random order,
+/// and estimates each board's chance of winning first.
+pub fn simulate(bingo: &Bingo, games: usize, seed: u64) -> Simulation {
+    let min = bingo.numbers.iter().copied().min().unwrap_or(0);
+    let max = bingo.numbers.iter().copied().max().unwrap_or(0);
+    let mut numbers: Vec<u32> = (min..=max).collect();
+
+    let mut rng = Rng::new(seed);
+    let mut wins = vec![0.0; bingo.boards.len()];
+    let mut no_winner = 0;
+
+    for _ in 0..games {
+        rng.shuffle(&mut numbers);
+        let mut game = bingo.clone();
+        game.numbers = VecDeque::from(numbers.clone());
+
+        loop {
+            match game.advance() {
+                AdvanceResult::Winners(winners) => {
+                    let share = 1.0 / winners.len() as f64;
+                    for win in winners {
+                        wins[win.board] += share;
+                    }
+                    break;
+                }
+                AdvanceResult::NoWinner => {}
+                AdvanceResult::NoMoreNumbers => {
+                    no_winner += 1;
+                    break;
+                }
+            }
+        }
+    }
+
+    let n = games.max(1) as f64;
+    let mut estimates: Vec<Estimate> = wins
+        .into_iter()
+        .enumerate()
+        .map(|(board, wins)| {
+            let p = wins / n;
+            Estimate {
+                board,
+                wins,
+                probability: p,
+                margin: 1.96 * (p * (1.0 - p) / n).sqrt(),
+            }
+        })
+        .collect();
+    estimates.sort_by(|a, b| b.probability.total_cmp(&a.probability));
+
+    Simulation {
+        games,
+        no_winner,
+        estimates,
+    }
+}
+
+pub fn print_simulation(sim: &Simulation, seed: u64) {
+    println!("{} games, seed {}", sim.games, seed);
+    println!(
+        "{:>6} {:>10} {:>9} {:>8}",
+        "board", "wins", "P(first)", "±95%"
+    );
+    for e in sim.estimates.iter() {
+        println!(
+            "{:>6} {:>10.1} {:>8.3}% {:>7.3}%",
+            e.board + 1,
+            e.wins,
+            e.probability * 100.0,
+            e.margin * 100.0
+        );
+    }
+    if sim.no_winner > 0 {
+        println!("no winner in {} games", sim.no_winner);
+    }
+}
@@ -1,17 +1,163 @@
+mod report;
+mod svg;
+mod sweep;
+
+use report::print_report;
 use shared::Grid;
+use std::env;
 use std::fmt;
+use std::fs;
 use std::io::prelude::*;
 
 type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
 
 fn main() -> Result<()> {
+    let options = Options::from_args()?;
     let input = read_input()?;
 
-    part1(&input);
+    match (options.mode, options.filter) {
+        (Mode::Report { top }, filter) => {
+            print_report(&input, filter.unwrap_or(LineFilter::Diagonals), top)?;
+        }
+        (Mode::Answers, Some(filter)) => {
+            let result = count_overlaps(&input, filter, options.method)?;
+            println!("{}: result = {}", filter, result);
+        }
+        (Mode::Answers, None) => {
+            part1(&input, options.method)?;
+            part2(&input, options.method)?;
+        }
+    }
+
+    if let Some(path) = &options.svg {
+        let filter = options.filter.unwrap_or(LineFilter::Diagonals);
+        let lines = filter.apply(&input)?;
+        let ocean_floor = draw_ocean_floor(&lines)?;
+        let title = format!(
+            "{} lines, {} overlaps",
+            filter,
+            ocean_floor.count_overlaps(2)
+        );
+        fs::write(path, svg::ocean_floor_svg(&lines, &ocean_floor, &title))
+            .map_err(|e| format!("{}: {}", path, e))?;
+    }
 
     Ok(())
 }
 
+const USAGE: &str = "usage: day5 [--lines axis|diagonals|all] [--sweep] [--svg <file>] < input
+       day5 report [--top <n>] [--lines axis|diagonals|all] [--svg <file>] < input";
+
+#[derive(Clone, Copy)]
+enum Mode {
+    /// Print the puzzle answers.
+    Answers,
+    /// Print a coverage histogram and the `top` most covered cells.
+    Report { top: usize },
+}
+
+struct Options {
+    mode: Mode,
+    /// Count overlaps for just these lines instead of printing both parts.
+    filter: Option<LineFilter>,
+    method: Method,
+    /// Where to draw the lines and their overlaps.
+    svg: Option<String>,
+}
+
+/// How to count overlaps.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Method {
+    /// Draw every line on a grid covering the whole ocean floor.
+    Grid,
+    /// Sweep across the segments themselves, for coordinates too large for a
+    /// grid.
+    Sweep,
+}
+
+impl Options {
+    fn from_args() -> Result<Options> {
+        let mut options = Options {
+            mode: Mode::Answers,
+            filter: None,
+            method: Method::Grid,
+            svg: None,
+        };
+        let mut args = env::args().skip(1);
+        while let Some(arg) = args.next() {
+            match (arg.as_str(), &mut options.mode) {
+                ("report", Mode::Answers) => options.mode = Mode::Report { top: 10 },
+                ("--top", Mode::Report { top }) => {
+                    *top = args.next().ok_or(USAGE)?.parse()?;
+                }
+                ("--lines", _) => {
+                    options.filter = Some(LineFilter::parse(&args.next().ok_or(USAGE)?)?);
+                }
+                ("--sweep", Mode::Answers) => options.method = Method::Sweep,
+                ("--svg", _) => options.svg = Some(args.next().ok_or(USAGE)?),
+                _ => return Err(USAGE.into()),
+            }
+        }
+        Ok(options)
+    }
+}
+
+/// Which vent lines to draw on the ocean floor.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum LineFilter {
+    /// Only horizontal and vertical lines.
+    Axis,
+    /// Horizontal, vertical and 45° lines.
+    Diagonals,
+    /// Every line.
+    All,
+}
+
+impl LineFilter {
+    fn parse(s: &str) -> Result<LineFilter> {
+        match s {
+            "axis" => Ok(LineFilter::Axis),
+            "diagonals" => Ok(LineFilter::Diagonals),
+            "all" => Ok(LineFilter::All),
+            _ => Err(format!("unknown line filter: {}\n{}", s, USAGE).into()),
+        }
+    }
+
+    fn apply(&self, lines: &[LineSegment]) -> Result<Vec<LineSegment>> {
+        let mut selected = Vec::new();
+        for &line in lines {
+            if self.includes(&line)? {
+                selected.push(line);
+            }
+        }
+        Ok(selected)
+    }
+
+    /// Part 1 ignores diagonals as the puzzle says, but a line at some other
+    /// slope is only allowed when every line was asked for.
+    fn includes(&self, line: &LineSegment) -> Result<bool> {
+        match (self, line.orientation()) {
+            (LineFilter::Diagonals, Orientation::Other) => Err(format!(
+                "line {} is neither horizontal, vertical nor at 45 degrees; use --lines all to draw it",
+                line
+            )
+            .into()),
+            (LineFilter::Axis, Orientation::Diagonal | Orientation::Other) => Ok(false),
+            _ => Ok(true),
+        }
+    }
+}
+
+impl fmt::Display for LineFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LineFilter::Axis => write!(f, "axis"),
+            LineFilter::Diagonals => write!(f, "diagonals"),
+            LineFilter::All => write!(f, "all"),
+        }
+    }
+}
+
 fn read_input() -> Result<Vec<LineSegment>> {
     let mut input = String::new();
     std::io::stdin().read_to_string(&mut input)?;
@@ -41,6 +187,22 @@ impl From<Vec<isize>> for Point {
     }
 }
 
+impl fmt::Display for Point {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{},{}", self.x, self.y)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Orientation {
+    Horizontal,
+    Vertical,
+    /// Exactly 45°.
+    Diagonal,
+    /// Any other slope, drawn with Bresenham's algorithm.
+    Other,
+}
+
 #[derive(Clone, Copy, PartialEq, Eq)]
 struct LineSegment(Point, Point);
 
@@ -60,6 +222,18 @@ impl LineSegment {
         self.0.x == self.1.x
     }
 
+    fn orientation(&self) -> Orientation {
+        if self.is_horizontal() {
+            Orientation::Horizontal
+        } else if self.is_vertical() {
+            Orientation::Vertical
+        } else if (self.1.x - self.0.x).abs() == (self.1.y - self.0.y).abs() {
+            Orientation::Diagonal
+        } else {
+            Orientation::Other
+        }
+    }
+
     fn x_dir(&self) -> isize {
         if self.0.x < self.1.x {
             1
@@ -80,44 +254,103 @@ impl LineSegment {
         }
     }
 
-    fn apply_to_grid<T, F>(&self, grid: &mut Grid<T>, f: F)
+    fn passes_through(&self, p: Point) -> bool {
+        let (min_x, max_x) = (self.0.x.min(self.1.x), self.0.x.max(self.1.x));
+        let (min_y, max_y) = (self.0.y.min(self.1.y), self.0.y.max(self.1.y));
+        (min_x..=max_x).contains(&p.x)
+            && (min_y..=max_y).contains(&p.y)
+            && self.points().any(|q| q == p)
+    }
+
+    /// Every lattice point on the line, from `self.0` to `self.1`.
+    fn points(&self) -> LinePoints {
+        LinePoints::new(self)
+    }
+
+    fn apply_to_grid<T, F>(&self, grid: &mut Grid<T>, f: F) -> Result<()>
     where
         T: Clone,
         F: Fn(isize, isize, &T) -> T,
     {
-        if self.is_horizontal() {
-            let x_dir = self.x_dir();
-            let y = self.0.y;
-            let mut x = self.0.x;
-            while x != self.1.x + x_dir {
-                let coords = (x as usize, y as usize);
-                let v = &grid[coords];
-                grid[coords] = f(x, y, v);
-                x += x_dir;
-            }
-        } else if self.is_vertical() {
-            let y_dir = self.y_dir();
-            let x = self.0.x;
-            let mut y = self.0.y;
-            while y != self.1.y + y_dir {
-                let coords = (x as usize, y as usize);
-                let v = &grid[coords];
-                grid[coords] = f(x, y, v);
-                y += y_dir;
-            }
-        } else {
-            let x_dir = self.x_dir();
-            let y_dir = self.y_dir();
-            let mut x = self.0.x;
-            let mut y = self.0.y;
-            while x != self.1.x + x_dir && y != self.1.y + y_dir {
-                let coords = (x as usize, y as usize);
-                let v = &grid[coords];
-                grid[coords] = f(x, y, v);
-                x += x_dir;
-                y += y_dir;
+        for Point { x, y } in self.points() {
+            match grid.try_get_mut(x, y) {
+                Some(v) => *v = f(x, y, v),
+                None => return Err(format!("line {} leaves the grid at {},{}", self, x, y).into()),
             }
         }
+
+        Ok(())
+    }
+}
+
+/// Bresenham's line algorithm, so lines at any angle come out as the cells a
+/// ruler would cross. Axis-aligned and 45° lines hit exactly the cells on
+/// them.
+struct LinePoints {
+    next: Option<Point>,
+    end: Point,
+    dx: isize,
+    dy: isize,
+    step_x: isize,
+    step_y: isize,
+    err: isize,
+}
+
+impl LinePoints {
+    fn new(line: &LineSegment) -> LinePoints {
+        let dx = (line.1.x - line.0.x).abs();
+        let dy = -(line.1.y - line.0.y).abs();
+        LinePoints {
+            next: Some(line.0),
+            end: line.1,
+            dx,
+            dy,
+            step_x: line.x_dir(),
+            step_y: line.y_dir(),
+            err: dx + dy,
+        }
+    }
+}
+
+impl Iterator for LinePoints {
+    type Item = Point;
+
+    fn next(&mut self) -> Option<Point> {
+        let point = self.next?;
+        if point == self.end {
+            self.next = None;
+            return Some(point);
+        }
+
+        let mut next = point;
+        let e2 = 2 * self.err;
+        if e2 >= self.dy {
+            self.err += self.dy;
+            next.x += self.step_x;
+        }
+        if e2 <= self.dx {
+            self.err += self.dx;
+            next.y += self.step_y;
+        }
+        self.next = Some(next);
+
+        Some(point)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = match self.next {
+            Some(p) => (self.end.x - p.x).abs().max((self.end.y - p.y).abs()) as usize + 1,
+            None => 0,
+        };
+        (len, Some(len))
+    }
+}
+
+impl ExactSizeIterator for LinePoints {}
+
+impl fmt::Display for LineSegment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} -> {}", self.0, self.1)
     }
 }
 
@@ -138,15 +371,48 @@ impl OceanFloor {
     fn count_overlaps(&self, min_overlap: isize) -> usize {
         self.0.iter().filter(|(_, _, &v)| v >= min_overlap).count()
     }
+
+    /// How many cells are covered by each number of lines, indexed by that
+    /// number.
+    fn histogram(&self) -> Vec<usize> {
+        let mut histogram = Vec::new();
+        for (_, _, &v) in self.0.iter() {
+            let v = v as usize;
+            if v >= histogram.len() {
+                histogram.resize(v + 1, 0);
+            }
+            histogram[v] += 1;
+        }
+        histogram
+    }
+
+    /// The `n` cells covered by the most lines, at least two, most covered
+    /// first and then in reading order.
+    fn most_covered(&self, n: usize) -> Vec<(usize, usize, isize)> {
+        let mut cells: Vec<(usize, usize, isize)> = self
+            .0
+            .iter()
+            .filter(|(_, _, &v)| v >= 2)
+            .map(|(x, y, &v)| (x, y, v))
+            .collect();
+        cells.sort_by_key(|&(x, y, v)| (std::cmp::Reverse(v), y, x));
+        cells.truncate(n);
+        cells
+    }
 }
 
 impl fmt::Display for OceanFloor {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         for (x, y, &v) in self.0.iter() {
             if x == 0 && y > 0 {
-                write!(f, "\n")?;
+                writeln!(f)?;
             }
-            let c = (if v == 0 { b'.' } else { b'0' + (v as u8) }) as char;
+            // Anything past 9 would run into punctuation.
+            let c = match v {
+                0 => '.',
+                1..=9 => (b'0' + v as u8) as char,
+                _ => '+',
+            };
             write!(f, "{} ", c)?;
         }
 
@@ -154,22 +420,88 @@ impl fmt::Display for OceanFloor {
     }
 }
 
-fn part1(lines: &[LineSegment]) {
-    let max_coord = lines
+/// The largest ocean floor that debug builds will draw to check the sweep.
+const CROSS_CHECK_MAX_COORD: isize = 2000;
+
+fn count_overlaps(lines: &[LineSegment], filter: LineFilter, method: Method) -> Result<usize> {
+    let lines = filter.apply(lines)?;
+
+    let result = match method {
+        Method::Grid => grid_overlaps(&lines)?,
+        Method::Sweep => sweep::count_overlaps(&lines, 2)?,
+    };
+
+    if cfg!(debug_assertions) {
+        let check = match method {
+            // The sweep only handles axis-aligned and 45° lines.
+            Method::Grid if lines.iter().all(|l| l.orientation() != Orientation::Other) => {
+                Some(sweep::count_overlaps(&lines, 2)?)
+            }
+            Method::Grid => None,
+            Method::Sweep
+                if min_coord(&lines) >= 0 && max_coord(&lines) <= CROSS_CHECK_MAX_COORD =>
+            {
+                Some(grid_overlaps(&lines)?)
+            }
+            Method::Sweep => None,
+        };
+        if let Some(check) = check {
+            assert_eq!(result, check, "grid and sweep disagree");
+        }
+    }
+
+    Ok(result)
+}
+
+fn min_coord(lines: &[LineSegment]) -> isize {
+    lines
+        .iter()
+        .flat_map(|l| vec![l.0.x, l.0.y, l.1.x, l.1.y])
+        .min()
+        .unwrap_or(0)
+}
+
+fn max_coord(lines: &[LineSegment]) -> isize {
+    lines
         .iter()
         .flat_map(|l| vec![l.0.x, l.0.y, l.1.x, l.1.y])
         .max()
-        .expect("no lines!") as usize;
+        .unwrap_or(0)
+}
 
-    let mut ocean_floor = OceanFloor::new(max_coord + 1);
+fn draw_ocean_floor(lines: &[LineSegment]) -> Result<OceanFloor> {
+    if min_coord(lines) < 0 {
+        return Err("the ocean floor grid can't hold negative coordinates; try --sweep".into());
+    }
+    let mut ocean_floor = OceanFloor::new(max_coord(lines) as usize + 1);
 
     for line in lines.iter() {
-        line.apply_to_grid(&mut ocean_floor.0, |_, _, v| v + 1);
+        line.apply_to_grid(&mut ocean_floor.0, |_, _, v| v + 1)?;
     }
 
+    Ok(ocean_floor)
+}
+
+fn grid_overlaps(lines: &[LineSegment]) -> Result<usize> {
+    let ocean_floor = draw_ocean_floor(lines)?;
+
     // println!("{}", ocean_floor);
 
-    let result = ocean_floor.count_overlaps(2);
+    Ok(ocean_floor.count_overlaps(2))
+}
+
+fn part1(lines: &[LineSegment], method: Method) -> Result<()> {
+    let result = count_overlaps(lines, LineFilter::Axis, method)?;
 
     println!("part1: result = {}", result);
+
+    Ok(())
+}
+
+fn part2(lines: &[LineSegment], method: Method) -> Result<()> {
+    let result = count_overlaps(lines, LineFilter::Diagonals, method)?;
+
+    println!("part2: result = {}", result);
+
+    Ok(())
 }
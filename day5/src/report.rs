@@ -0,0 +1,56 @@
+use crate::{draw_ocean_floor, LineFilter, LineSegment, Point, Result};
+
+/// Describes how the lines selected by `filter` cover the ocean floor: how
+/// many cells each number of lines covers, the `top` most covered cells, and
+/// which pairs of lines cross at each of them.
+pub fn print_report(lines: &[LineSegment], filter: LineFilter, top: usize) -> Result<()> {
+    // Numbered by their line in the input.
+    let mut selected: Vec<(usize, LineSegment)> = Vec::new();
+    for (i, &line) in lines.iter().enumerate() {
+        if filter.includes(&line)? {
+            selected.push((i + 1, line));
+        }
+    }
+    let segments: Vec<LineSegment> = selected.iter().map(|&(_, l)| l).collect();
+    let ocean_floor = draw_ocean_floor(&segments)?;
+
+    println!(
+        "{} lines: {} of {} segments",
+        filter,
+        selected.len(),
+        lines.len()
+    );
+
+    println!();
+    println!("{:>6} {:>8} {:>9}", "lines", "cells", "at least");
+    let histogram = ocean_floor.histogram();
+    let mut at_least: usize = histogram.iter().skip(1).sum();
+    for (count, &cells) in histogram.iter().enumerate().skip(1) {
+        println!("{:>6} {:>8} {:>9}", count, cells, at_least);
+        at_least -= cells;
+    }
+
+    println!();
+    println!("most covered:");
+    for (x, y, count) in ocean_floor.most_covered(top) {
+        let p = Point {
+            x: x as isize,
+            y: y as isize,
+        };
+        let through: Vec<usize> = selected
+            .iter()
+            .filter(|(_, l)| l.passes_through(p))
+            .map(|&(n, _)| n)
+            .collect();
+        debug_assert_eq!(through.len(), count as usize);
+
+        println!("{}: {} lines", p, count);
+        for (i, &a) in through.iter().enumerate() {
+            for &b in through[i + 1..].iter() {
+                println!("  #{} {} x #{} {}", a, lines[a - 1], b, lines[b - 1]);
+            }
+        }
+    }
+
+    Ok(())
+}
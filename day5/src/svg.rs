@@ -0,0 +1,105 @@
+use crate::{LineSegment, OceanFloor};
+
+const SIZE: f64 = 1000.0;
+const MARGIN: f64 = 20.0;
+const LEGEND_WIDTH: f64 = 120.0;
+
+/// Colours for cells covered by 2, 3, ... lines; the last one is used for
+/// everything above.
+const PALETTE: [&str; 6] = ["#fc0", "#f80", "#f40", "#c00", "#808", "#305"];
+
+fn bucket(count: isize) -> usize {
+    ((count - 2) as usize).min(PALETTE.len() - 1)
+}
+
+/// Draws `lines` over `ocean_floor`, with every cell at least two of them
+/// cross filled in by how many do.
+pub fn ocean_floor_svg(lines: &[LineSegment], ocean_floor: &OceanFloor, title: &str) -> String {
+    let grid = &ocean_floor.0;
+    let scale = SIZE / grid.width().max(1) as f64;
+    // Cell centres, so lines run through the middle of the cells they cover.
+    let pos = |v: isize| MARGIN + (v as f64 + 0.5) * scale;
+
+    let width = SIZE + 2.0 * MARGIN + LEGEND_WIDTH;
+    let height = SIZE + 2.0 * MARGIN + 20.0;
+
+    let mut svg = String::new();
+    svg.push_str(&format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" viewBox=\"0 0 {w} {h}\">\n",
+        w = width,
+        h = height
+    ));
+    svg.push_str(&format!(
+        "<rect width=\"{}\" height=\"{}\" fill=\"white\"/>\n",
+        width, height
+    ));
+    svg.push_str(&format!(
+        "<rect x=\"{m}\" y=\"{m}\" width=\"{s}\" height=\"{s}\" fill=\"#024\"/>\n",
+        m = MARGIN,
+        s = SIZE
+    ));
+    svg.push_str(&format!(
+        "<text x=\"{}\" y=\"{}\" font-family=\"sans-serif\" font-size=\"14\">{}</text>\n",
+        MARGIN,
+        SIZE + 2.0 * MARGIN + 10.0,
+        title
+    ));
+
+    svg.push_str(&format!(
+        "<g stroke=\"#7ab\" stroke-width=\"{:.2}\" stroke-linecap=\"square\" opacity=\"0.6\">\n",
+        scale.clamp(0.5, 2.0)
+    ));
+    for line in lines {
+        svg.push_str(&format!(
+            "<line x1=\"{:.1}\" y1=\"{:.1}\" x2=\"{:.1}\" y2=\"{:.1}\"/>\n",
+            pos(line.0.x),
+            pos(line.0.y),
+            pos(line.1.x),
+            pos(line.1.y)
+        ));
+    }
+    svg.push_str("</g>\n");
+
+    let mut totals = [0; PALETTE.len()];
+    for (x, y, &count) in grid.iter().filter(|(_, _, &v)| v >= 2) {
+        totals[bucket(count)] += 1;
+        svg.push_str(&format!(
+            "<rect x=\"{:.2}\" y=\"{:.2}\" width=\"{s:.2}\" height=\"{s:.2}\" fill=\"{}\"><title>{},{}: {}</title></rect>\n",
+            MARGIN + x as f64 * scale,
+            MARGIN + y as f64 * scale,
+            PALETTE[bucket(count)],
+            x,
+            y,
+            count,
+            s = scale.max(1.0)
+        ));
+    }
+
+    let legend_x = SIZE + 2.0 * MARGIN;
+    svg.push_str(&format!(
+        "<text x=\"{}\" y=\"{}\" font-family=\"sans-serif\" font-size=\"12\">lines per cell</text>\n",
+        legend_x, MARGIN + 10.0
+    ));
+    for (i, (fill, total)) in PALETTE.iter().zip(totals).enumerate() {
+        let y = MARGIN + 25.0 + i as f64 * 20.0;
+        let label = if i == PALETTE.len() - 1 {
+            format!("{}+", i + 2)
+        } else {
+            format!("{}", i + 2)
+        };
+        svg.push_str(&format!(
+            "<rect x=\"{}\" y=\"{}\" width=\"12\" height=\"12\" fill=\"{}\"/>\n",
+            legend_x, y, fill
+        ));
+        svg.push_str(&format!(
+            "<text x=\"{}\" y=\"{}\" font-family=\"sans-serif\" font-size=\"11\">{} ({})</text>\n",
+            legend_x + 18.0,
+            y + 10.0,
+            label,
+            total
+        ));
+    }
+
+    svg.push_str("</svg>\n");
+    svg
+}
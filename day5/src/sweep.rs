@@ -0,0 +1,86 @@
+use crate::{LineSegment, Orientation, Result};
+
+/// Counts the lattice points covered by at least `min_overlap` of `lines`
+/// without building a grid: a line sweeps down one row at a time, and each
+/// row is solved from the x-ranges of the segments crossing it. Memory grows
+/// with the number of segments, not with the size of the ocean floor.
+pub fn count_overlaps(lines: &[LineSegment], min_overlap: usize) -> Result<usize> {
+    if let Some(line) = lines.iter().find(|l| l.orientation() == Orientation::Other) {
+        return Err(format!(
+            "line {} can't be swept, only axis-aligned and 45 degree lines can",
+            line
+        )
+        .into());
+    }
+
+    // Segments in the order the sweep reaches them.
+    let mut pending: Vec<&LineSegment> = lines.iter().collect();
+    pending.sort_by_key(|l| std::cmp::Reverse(top(l)));
+
+    let mut active: Vec<&LineSegment> = Vec::with_capacity(lines.len());
+    let mut events: Vec<(isize, isize)> = Vec::with_capacity(lines.len() * 2);
+    let mut count = 0;
+
+    let mut y = match pending.last() {
+        Some(line) => top(line),
+        None => return Ok(0),
+    };
+
+    loop {
+        while pending.last().is_some_and(|l| top(l) == y) {
+            active.extend(pending.pop());
+        }
+        active.retain(|l| bottom(l) >= y);
+
+        if active.is_empty() {
+            match pending.last() {
+                // Skip straight past empty rows.
+                Some(line) => {
+                    y = top(line);
+                    continue;
+                }
+                None => break,
+            }
+        }
+
+        events.clear();
+        for line in active.iter() {
+            let (from, to) = span_at(line, y);
+            events.push((from, 1));
+            events.push((to + 1, -1));
+        }
+        events.sort_unstable();
+
+        let mut depth = 0;
+        for (i, &(x, delta)) in events.iter().enumerate() {
+            depth += delta;
+            if depth >= min_overlap as isize {
+                if let Some(&(next_x, _)) = events.get(i + 1) {
+                    count += (next_x - x) as usize;
+                }
+            }
+        }
+
+        y += 1;
+    }
+
+    Ok(count)
+}
+
+fn top(line: &LineSegment) -> isize {
+    line.0.y.min(line.1.y)
+}
+
+fn bottom(line: &LineSegment) -> isize {
+    line.0.y.max(line.1.y)
+}
+
+/// The inclusive range of x covered by `line` on row `y`.
+fn span_at(line: &LineSegment, y: isize) -> (isize, isize) {
+    if line.is_horizontal() {
+        (line.0.x.min(line.1.x), line.0.x.max(line.1.x))
+    } else {
+        let x = line.0.x + line.x_dir() * (y - line.0.y) * line.y_dir();
+        (x, x)
+    }
+}
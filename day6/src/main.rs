@@ -1,16 +1,72 @@
+use std::env;
 use std::io::prelude::*;
 
 type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
 
 fn main() -> Result<()> {
+    let options = Options::from_args()?;
     let input = read_input()?;
+    let model = options.model;
+    model.check_timers(&input)?;
 
-    part1(&input);
-    part2(&input);
+    match options.days {
+        Some(days) => {
+            let num_fish = solve(&input, days, model, options.modulus)?;
+            match options.modulus {
+                Some(m) => println!("day {}: num fish = {} (mod {})", days, num_fish, m),
+                None => println!("day {}: num fish = {}", days, num_fish),
+            }
+        }
+        None => {
+            part1(&input, model)?;
+            part2(&input, model)?;
+        }
+    }
 
     Ok(())
 }
 
+const USAGE: &str =
+    "usage: day6 [--days <n>] [--reset <timer>] [--newborn <timer>] [--modulo <m>] < input";
+
+struct Options {
+    /// Count the fish after this many days instead of printing both parts.
+    days: Option<u64>,
+    model: Model,
+    /// Give counts modulo this, for day counts whose answers don't fit in a
+    /// u128.
+    modulus: Option<u128>,
+}
+
+impl Options {
+    fn from_args() -> Result<Options> {
+        let mut options = Options {
+            days: None,
+            model: Model::STANDARD,
+            modulus: None,
+        };
+        let mut args = env::args().skip(1);
+        while let Some(arg) = args.next() {
+            let value = args.next().ok_or(USAGE)?;
+            match arg.as_str() {
+                "--days" => options.days = Some(value.parse()?),
+                "--reset" => options.model.reset = value.parse()?,
+                "--newborn" => options.model.newborn = value.parse()?,
+                "--modulo" => {
+                    let m: u128 = value.parse()?;
+                    // Products of two residues have to fit in a u128.
+                    if !(2..=u64::MAX as u128).contains(&m) {
+                        return Err(format!("modulus must be between 2 and {}", u64::MAX).into());
+                    }
+                    options.modulus = Some(m);
+                }
+                _ => return Err(USAGE.into()),
+            }
+        }
+        Ok(options)
+    }
+}
+
 fn read_input() -> Result<Vec<usize>> {
     let mut input = String::new();
     std::io::stdin().read_to_string(&mut input)?;
@@ -21,31 +77,182 @@ fn read_input() -> Result<Vec<usize>> {
         .collect())
 }
 
-fn calculate(initial_timers: &[usize], num_days: usize) -> usize {
-    let mut counts = [0; 9];
-    
-    for &timer in initial_timers {
-        counts[timer] += 1
+/// How a fish's timer behaves: a fish whose timer is 0 resets it to `reset`
+/// and spawns a fish with timer `newborn`.
+#[derive(Clone, Copy)]
+struct Model {
+    reset: usize,
+    newborn: usize,
+}
+
+impl Model {
+    const STANDARD: Model = Model {
+        reset: 6,
+        newborn: 8,
+    };
+
+    /// Number of distinct timer values, `0..=max(reset, newborn)`.
+    fn num_timers(&self) -> usize {
+        self.reset.max(self.newborn) + 1
+    }
+
+    fn check_timers(&self, timers: &[usize]) -> Result<()> {
+        match timers.iter().find(|&&t| t >= self.num_timers()) {
+            Some(t) => Err(format!(
+                "timer {} is higher than both the reset ({}) and newborn ({}) timers",
+                t, self.reset, self.newborn
+            )
+            .into()),
+            None => Ok(()),
+        }
+    }
+
+    fn initial_counts(&self, initial_timers: &[usize]) -> Vec<u128> {
+        let mut counts = vec![0; self.num_timers()];
+        for &timer in initial_timers {
+            counts[timer] += 1
+        }
+        counts
+    }
+
+    /// One day as a matrix: `next[i]` is the sum over `j` of
+    /// `matrix[i][j] * counts[j]`.
+    fn day_matrix(&self) -> Matrix {
+        let n = self.num_timers();
+        let mut matrix = Matrix::zero(n);
+        for i in 0..n - 1 {
+            matrix.0[i][i + 1] = 1;
+        }
+        matrix.0[self.reset][0] += 1;
+        matrix.0[self.newborn][0] += 1;
+        matrix
+    }
+}
+
+/// Checked, or modulo `Some(m)`, so overflow is reported instead of wrapping.
+fn mul_add(acc: u128, a: u128, b: u128, modulus: Option<u128>) -> Result<u128> {
+    match modulus {
+        Some(m) => Ok((acc + a * b % m) % m),
+        None => a
+            .checked_mul(b)
+            .and_then(|p| p.checked_add(acc))
+            .ok_or_else(|| "fish count overflowed a u128; try --modulo".into()),
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+struct Matrix(Vec<Vec<u128>>);
+
+impl Matrix {
+    fn zero(n: usize) -> Matrix {
+        Matrix(vec![vec![0; n]; n])
+    }
+
+    fn identity(n: usize) -> Matrix {
+        let mut m = Matrix::zero(n);
+        for i in 0..n {
+            m.0[i][i] = 1;
+        }
+        m
     }
 
+    fn mul(&self, other: &Matrix, modulus: Option<u128>) -> Result<Matrix> {
+        let n = self.0.len();
+        let mut result = Matrix::zero(n);
+        for i in 0..n {
+            for j in 0..n {
+                let mut sum = 0;
+                for k in 0..n {
+                    sum = mul_add(sum, self.0[i][k], other.0[k][j], modulus)?;
+                }
+                result.0[i][j] = sum;
+            }
+        }
+        Ok(result)
+    }
+
+    fn mul_vec(&self, v: &[u128], modulus: Option<u128>) -> Result<Vec<u128>> {
+        self.0
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .zip(v)
+                    .try_fold(0, |sum, (&a, &b)| mul_add(sum, a, b, modulus))
+            })
+            .collect()
+    }
+
+    /// Raises the matrix to `exp` by repeated squaring.
+    fn pow(&self, mut exp: u64, modulus: Option<u128>) -> Result<Matrix> {
+        let mut result = Matrix::identity(self.0.len());
+        let mut base = self.clone();
+        while exp > 0 {
+            if exp & 1 == 1 {
+                result = result.mul(&base, modulus)?;
+            }
+            exp >>= 1;
+            if exp > 0 {
+                base = base.mul(&base, modulus)?;
+            }
+        }
+        Ok(result)
+    }
+}
+
+/// The reference solution: one day at a time.
+fn calculate(initial_timers: &[usize], num_days: u64, model: Model) -> Option<u128> {
+    let mut counts = model.initial_counts(initial_timers);
+    let last = counts.len() - 1;
+
     for _ in 0..num_days {
         let spawning = counts[0];
-        for i in 0..8 {
-            counts[i] = counts[i+1];
+        counts.rotate_left(1);
+        counts[last] = 0;
+        counts[model.reset] = counts[model.reset].checked_add(spawning)?;
+        counts[model.newborn] = counts[model.newborn].checked_add(spawning)?;
+    }
+
+    counts.iter().try_fold(0u128, |sum, &c| sum.checked_add(c))
+}
+
+/// Above this many days, skip checking `solve` against `calculate`.
+const CROSS_CHECK_DAYS: u64 = 1000;
+
+/// Counts the fish after `num_days` in O(log num_days) matrix multiplications.
+fn solve(
+    initial_timers: &[usize],
+    num_days: u64,
+    model: Model,
+    modulus: Option<u128>,
+) -> Result<u128> {
+    let counts = model.initial_counts(initial_timers);
+    let days = model.day_matrix().pow(num_days, modulus)?;
+    let result = days
+        .mul_vec(&counts, modulus)?
+        .into_iter()
+        .try_fold(0, |sum, c| mul_add(sum, c, 1, modulus))?;
+
+    if cfg!(debug_assertions) && num_days <= CROSS_CHECK_DAYS {
+        if let Some(expected) = calculate(initial_timers, num_days, model) {
+            assert_eq!(
+                result,
+                modulus.map_or(expected, |m| expected % m),
+                "matrix power and day-by-day counts disagree"
+            );
         }
-        counts[6] += spawning;
-        counts[8] = spawning;
     }
 
-    counts.iter().sum()
+    Ok(result)
 }
 
-fn part1(input: &[usize]) {
-    let num_fish = calculate(input, 80);
+fn part1(input: &[usize], model: Model) -> Result<()> {
+    let num_fish = solve(input, 80, model, None)?;
     println!("part1: num fish = {}", num_fish);
+    Ok(())
 }
 
-fn part2(input: &[usize]) {
-    let num_fish = calculate(input, 256);
+fn part2(input: &[usize], model: Model) -> Result<()> {
+    let num_fish = solve(input, 256, model, None)?;
     println!("part2: num fish = {}", num_fish);
+    Ok(())
 }
@@ -1,4 +1,4 @@
-use shared::Grid;
+use shared::{BitGrid, Grid};
 use std::io::prelude::*;
 
 type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
@@ -52,12 +52,12 @@ fn part1(input: &Grid<u8>) {
     println!("part1: result = {}", sum_risk_levels);
 }
 
-fn discover_basin_size(g: &Grid<u8>, counted: &mut Grid<bool>, x: usize, y: usize) -> usize {
+fn discover_basin_size(g: &Grid<u8>, counted: &mut BitGrid, x: usize, y: usize) -> usize {
     let mut count = 0;
 
-    if !counted[(x, y)] {
+    if !counted.get(x, y) {
         count += 1;
-        counted[(x, y)] = true;
+        counted.set(x, y, true);
     }
 
     let center = g[(x, y)];
@@ -86,7 +86,7 @@ fn discover_basin_size(g: &Grid<u8>, counted: &mut Grid<bool>, x: usize, y: usiz
 }
 
 fn part2(input: &Grid<u8>) {
-    let mut counted = Grid::new(input.width(), input.height(), false);
+    let mut counted = BitGrid::new(input.width(), input.height(), false);
     let low_points = input.iter().filter(|&(x, y, _)| is_low_point(input, x, y));
     let mut basin_sizes = low_points
         .into_iter()
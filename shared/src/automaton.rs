@@ -0,0 +1,244 @@
+use crate::Grid;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Neighbourhood {
+    /// The 4 cells sharing an edge.
+    VonNeumann,
+    /// The 8 cells sharing an edge or a corner.
+    Moore,
+}
+
+const VON_NEUMANN_OFFSETS: [(isize, isize); 4] = [(0, -1), (-1, 0), (1, 0), (0, 1)];
+
+const MOORE_OFFSETS: [(isize, isize); 8] = [
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+    (-1, 0),
+    (1, 0),
+    (-1, 1),
+    (0, 1),
+    (1, 1),
+];
+
+impl Neighbourhood {
+    /// The signed `(dx, dy)` offsets of the neighbourhood.
+    pub fn offsets(self) -> &'static [(isize, isize)] {
+        match self {
+            Neighbourhood::VonNeumann => &VON_NEUMANN_OFFSETS,
+            Neighbourhood::Moore => &MOORE_OFFSETS,
+        }
+    }
+
+    /// Coordinates of the in-bounds neighbours of `(x, y)`.
+    pub fn coords(
+        self,
+        width: usize,
+        height: usize,
+        x: usize,
+        y: usize,
+    ) -> impl Iterator<Item = (usize, usize)> {
+        self.offsets().iter().filter_map(move |&(dx, dy)| {
+            let nx = x as isize + dx;
+            let ny = y as isize + dy;
+            if nx < 0 || ny < 0 || nx as usize >= width || ny as usize >= height {
+                None
+            } else {
+                Some((nx as usize, ny as usize))
+            }
+        })
+    }
+
+    pub fn values<T: Clone>(self, grid: &Grid<T>, x: usize, y: usize) -> impl Iterator<Item = &T> {
+        self.coords(grid.width(), grid.height(), x, y)
+            .map(move |(nx, ny)| &grid[(nx, ny)])
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct StepStats {
+    /// 1-based number of the step these stats describe.
+    pub step: usize,
+    /// Cells whose value differs from the previous step.
+    pub changed: usize,
+    /// Cells that fired during the cascade phase.
+    pub fired: usize,
+}
+
+type Rule<'a, T> = Box<dyn FnMut(&Grid<T>, usize, usize) -> T + 'a>;
+
+type StepHook<'a, T> = Box<dyn FnMut(&StepStats, &Grid<T>) + 'a>;
+
+struct Cascade<'a, T> {
+    neighbourhood: Neighbourhood,
+    fires: Box<dyn Fn(&T) -> bool + 'a>,
+    propagate: Box<dyn Fn(&mut T) + 'a>,
+    settle: Box<dyn Fn(&mut T) + 'a>,
+}
+
+/// Steps a `Grid<T>` through a cellular automaton.
+///
+/// Each step computes every cell's new value from the previous generation
+/// with the rule closure, writing into a second buffer. If a cascade is
+/// configured, any cell that then `fires` propagates to its neighbours, which
+/// may fire in turn; each cell fires at most once per step, and fired cells
+/// are passed to `settle` once the cascade has died out.
+pub struct Automaton<'a, T> {
+    current: Grid<T>,
+    next: Grid<T>,
+    step: usize,
+    rule: Rule<'a, T>,
+    cascade: Option<Cascade<'a, T>>,
+    hooks: Vec<StepHook<'a, T>>,
+}
+
+impl<'a, T: Clone + PartialEq> Automaton<'a, T> {
+    pub fn new<R>(grid: Grid<T>, rule: R) -> Automaton<'a, T>
+    where
+        R: FnMut(&Grid<T>, usize, usize) -> T + 'a,
+    {
+        Automaton {
+            next: grid.clone(),
+            current: grid,
+            step: 0,
+            rule: Box::new(rule),
+            cascade: None,
+            hooks: Vec::new(),
+        }
+    }
+
+    pub fn with_cascade<F, P, S>(
+        mut self,
+        neighbourhood: Neighbourhood,
+        fires: F,
+        propagate: P,
+        settle: S,
+    ) -> Automaton<'a, T>
+    where
+        F: Fn(&T) -> bool + 'a,
+        P: Fn(&mut T) + 'a,
+        S: Fn(&mut T) + 'a,
+    {
+        self.cascade = Some(Cascade {
+            neighbourhood,
+            fires: Box::new(fires),
+            propagate: Box::new(propagate),
+            settle: Box::new(settle),
+        });
+        self
+    }
+
+    /// Registers a hook that is called with the stats and the new grid after
+    /// every step.
+    pub fn on_step<H>(mut self, hook: H) -> Automaton<'a, T>
+    where
+        H: FnMut(&StepStats, &Grid<T>) + 'a,
+    {
+        self.hooks.push(Box::new(hook));
+        self
+    }
+
+    pub fn grid(&self) -> &Grid<T> {
+        &self.current
+    }
+
+    pub fn into_grid(self) -> Grid<T> {
+        self.current
+    }
+
+    /// Number of steps run so far.
+    pub fn steps(&self) -> usize {
+        self.step
+    }
+
+    pub fn step(&mut self) -> StepStats {
+        for y in 0..self.current.height() {
+            for x in 0..self.current.width() {
+                self.next[(x, y)] = (self.rule)(&self.current, x, y);
+            }
+        }
+
+        let fired = match &self.cascade {
+            Some(cascade) => cascade.run(&mut self.next),
+            None => 0,
+        };
+
+        let changed = self
+            .current
+            .cells
+            .iter()
+            .zip(self.next.cells.iter())
+            .filter(|(a, b)| a != b)
+            .count();
+
+        std::mem::swap(&mut self.current, &mut self.next);
+        self.step += 1;
+
+        let stats = StepStats {
+            step: self.step,
+            changed,
+            fired,
+        };
+        for hook in self.hooks.iter_mut() {
+            hook(&stats, &self.current);
+        }
+        stats
+    }
+
+    pub fn run(&mut self, steps: usize) -> Vec<StepStats> {
+        (0..steps).map(|_| self.step()).collect()
+    }
+
+    /// Steps until `pred` holds for a step's stats and resulting grid, and
+    /// returns that step's stats. Never returns if `pred` never holds.
+    pub fn run_until<P>(&mut self, mut pred: P) -> StepStats
+    where
+        P: FnMut(&StepStats, &Grid<T>) -> bool,
+    {
+        loop {
+            let stats = self.step();
+            if pred(&stats, &self.current) {
+                return stats;
+            }
+        }
+    }
+}
+
+impl<'a, T: Clone> Cascade<'a, T> {
+    fn run(&self, grid: &mut Grid<T>) -> usize {
+        let mut fired = Grid::new(grid.width(), grid.height(), false);
+        let mut pending = Vec::new();
+
+        for y in 0..grid.height() {
+            for x in 0..grid.width() {
+                if (self.fires)(&grid[(x, y)]) {
+                    fired[(x, y)] = true;
+                    pending.push((x, y));
+                }
+            }
+        }
+
+        let mut count = 0;
+        while let Some((x, y)) = pending.pop() {
+            count += 1;
+            for (nx, ny) in self.neighbourhood.coords(grid.width(), grid.height(), x, y) {
+                (self.propagate)(&mut grid[(nx, ny)]);
+                if !fired[(nx, ny)] && (self.fires)(&grid[(nx, ny)]) {
+                    fired[(nx, ny)] = true;
+                    pending.push((nx, ny));
+                }
+            }
+        }
+
+        for (cell, _) in grid
+            .cells
+            .iter_mut()
+            .zip(fired.cells.iter())
+            .filter(|(_, &f)| f)
+        {
+            (self.settle)(cell);
+        }
+
+        count
+    }
+}
@@ -0,0 +1,237 @@
+use crate::Grid;
+use std::ops::{BitAndAssign, BitOrAssign, BitXorAssign};
+
+const WORD_BITS: usize = u64::BITS as usize;
+
+/// A grid of booleans packed one bit per cell. Each row starts on a fresh
+/// `u64` word, so whole-row operations never have to shift.
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct BitGrid {
+    width: usize,
+    height: usize,
+    words_per_row: usize,
+    words: Vec<u64>,
+}
+
+impl BitGrid {
+    pub fn new(width: usize, height: usize, initial_value: bool) -> BitGrid {
+        let words_per_row = width.div_ceil(WORD_BITS);
+        let mut g = BitGrid {
+            width,
+            height,
+            words_per_row,
+            words: vec![0; words_per_row * height],
+        };
+        if initial_value {
+            g.fill(true);
+        }
+        g
+    }
+
+    pub fn new_square(size: usize, initial_value: bool) -> BitGrid {
+        BitGrid::new(size, size, initial_value)
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> bool {
+        let (word, mask) = self.bit_position(x, y);
+        self.words[word] & mask != 0
+    }
+
+    pub fn set(&mut self, x: usize, y: usize, value: bool) {
+        let (word, mask) = self.bit_position(x, y);
+        if value {
+            self.words[word] |= mask;
+        } else {
+            self.words[word] &= !mask;
+        }
+    }
+
+    pub fn try_get(&self, x: isize, y: isize) -> Option<bool> {
+        if x < 0 || y < 0 || x as usize >= self.width || y as usize >= self.height {
+            None
+        } else {
+            Some(self.get(x as usize, y as usize))
+        }
+    }
+
+    /// Sets the cell if it is in bounds, returning whether it was.
+    pub fn try_set(&mut self, x: isize, y: isize, value: bool) -> bool {
+        if x < 0 || y < 0 || x as usize >= self.width || y as usize >= self.height {
+            false
+        } else {
+            self.set(x as usize, y as usize, value);
+            true
+        }
+    }
+
+    pub fn fill(&mut self, value: bool) {
+        let width = self.width;
+        for y in 0..self.height {
+            for (i, word) in self.row_words_mut(y).iter_mut().enumerate() {
+                let bits = (width - i * WORD_BITS).min(WORD_BITS);
+                *word = if !value {
+                    0
+                } else if bits == WORD_BITS {
+                    u64::MAX
+                } else {
+                    (1 << bits) - 1
+                };
+            }
+        }
+    }
+
+    pub fn iter(&self) -> BitGridIter<'_> {
+        BitGridIter {
+            grid: self,
+            x: 0,
+            y: 0,
+        }
+    }
+
+    /// Number of set cells in the whole grid.
+    pub fn count_ones(&self) -> usize {
+        self.words.iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    pub fn count_ones_in_row(&self, y: usize) -> usize {
+        self.row_words(y)
+            .iter()
+            .map(|w| w.count_ones() as usize)
+            .sum()
+    }
+
+    /// The packed words for row `y`, with cell `x` at bit `x % 64` of word
+    /// `x / 64`. Bits past the width are always clear.
+    pub fn row_words(&self, y: usize) -> &[u64] {
+        let start = y * self.words_per_row;
+        &self.words[start..start + self.words_per_row]
+    }
+
+    pub fn and_row(&mut self, y: usize, other: &BitGrid, other_y: usize) {
+        self.combine_row(y, other, other_y, |a, b| a & b);
+    }
+
+    pub fn or_row(&mut self, y: usize, other: &BitGrid, other_y: usize) {
+        self.combine_row(y, other, other_y, |a, b| a | b);
+    }
+
+    pub fn xor_row(&mut self, y: usize, other: &BitGrid, other_y: usize) {
+        self.combine_row(y, other, other_y, |a, b| a ^ b);
+    }
+
+    fn combine_row<F>(&mut self, y: usize, other: &BitGrid, other_y: usize, op: F)
+    where
+        F: Fn(u64, u64) -> u64,
+    {
+        assert!(self.width == other.width, "rows have different widths");
+        let src = other.row_words(other_y);
+        for (dst, &src) in self.row_words_mut(y).iter_mut().zip(src.iter()) {
+            *dst = op(*dst, src);
+        }
+    }
+
+    fn row_words_mut(&mut self, y: usize) -> &mut [u64] {
+        let start = y * self.words_per_row;
+        &mut self.words[start..start + self.words_per_row]
+    }
+
+    fn bit_position(&self, x: usize, y: usize) -> (usize, u64) {
+        assert!(
+            x < self.width && y < self.height,
+            "coordinate out of bounds"
+        );
+        let word = y * self.words_per_row + x / WORD_BITS;
+        (word, 1 << (x % WORD_BITS))
+    }
+}
+
+pub struct BitGridIter<'a> {
+    grid: &'a BitGrid,
+    x: usize,
+    y: usize,
+}
+
+impl<'a> Iterator for BitGridIter<'a> {
+    type Item = (usize, usize, bool);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.y < self.grid.height && self.grid.width > 0 {
+            let (x, y, val) = (self.x, self.y, self.grid.get(self.x, self.y));
+            self.x += 1;
+            if self.x == self.grid.width {
+                self.x = 0;
+                self.y += 1;
+            }
+            Some((x, y, val))
+        } else {
+            None
+        }
+    }
+}
+
+impl std::ops::Index<(usize, usize)> for BitGrid {
+    type Output = bool;
+
+    fn index(&self, coords: (usize, usize)) -> &bool {
+        let (x, y) = coords;
+        if self.get(x, y) {
+            &true
+        } else {
+            &false
+        }
+    }
+}
+
+impl BitAndAssign<&BitGrid> for BitGrid {
+    fn bitand_assign(&mut self, other: &BitGrid) {
+        for y in 0..self.height {
+            self.and_row(y, other, y);
+        }
+    }
+}
+
+impl BitOrAssign<&BitGrid> for BitGrid {
+    fn bitor_assign(&mut self, other: &BitGrid) {
+        for y in 0..self.height {
+            self.or_row(y, other, y);
+        }
+    }
+}
+
+impl BitXorAssign<&BitGrid> for BitGrid {
+    fn bitxor_assign(&mut self, other: &BitGrid) {
+        for y in 0..self.height {
+            self.xor_row(y, other, y);
+        }
+    }
+}
+
+impl From<&Grid<bool>> for BitGrid {
+    fn from(g: &Grid<bool>) -> Self {
+        let mut bits = BitGrid::new(g.width(), g.height(), false);
+        for (x, y, &v) in g.iter() {
+            if v {
+                bits.set(x, y, true);
+            }
+        }
+        bits
+    }
+}
+
+impl From<&BitGrid> for Grid<bool> {
+    fn from(bits: &BitGrid) -> Self {
+        let mut g = Grid::new(bits.width(), bits.height(), false);
+        for (x, y, v) in bits.iter() {
+            g[(x, y)] = v;
+        }
+        g
+    }
+}
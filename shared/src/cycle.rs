@@ -0,0 +1,136 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+
+/// The result of iterating a state-transition function until a state repeats.
+///
+/// States `0..start` form the tail leading into the cycle, and states
+/// `start..start + period` repeat forever after.
+pub struct Cycle<S> {
+    pub start: usize,
+    pub period: usize,
+    states: Vec<S>,
+}
+
+impl<S> Cycle<S> {
+    /// The state after `step` applications of the transition function, for
+    /// any `step`, however far in the future.
+    pub fn state_at(&self, step: usize) -> &S {
+        if step < self.states.len() {
+            &self.states[step]
+        } else {
+            &self.states[self.start + (step - self.start) % self.period]
+        }
+    }
+
+    /// Every distinct state seen, in the order they were produced.
+    pub fn states(&self) -> &[S] {
+        &self.states
+    }
+}
+
+/// Runs `next` from `initial`, remembering every state in a hash map, until a
+/// state repeats. Gives up and returns `None` if no state has repeated after
+/// `max_steps` transitions.
+pub fn find_cycle<S, F>(initial: S, mut next: F, max_steps: usize) -> Option<Cycle<S>>
+where
+    S: Clone + Hash + Eq,
+    F: FnMut(&S) -> S,
+{
+    let mut seen = HashMap::new();
+    let mut states = Vec::new();
+    let mut state = initial;
+
+    for step in 0..=max_steps {
+        if let Some(&start) = seen.get(&state) {
+            return Some(Cycle {
+                start,
+                period: step - start,
+                states,
+            });
+        }
+        let following = next(&state);
+        seen.insert(state.clone(), step);
+        states.push(state);
+        state = following;
+    }
+
+    None
+}
+
+/// Finds the `(start, period)` of the cycle reached from `initial` with
+/// Brent's algorithm, which only holds two states at a time, so it suits
+/// states too big to keep a history of. Only needs `PartialEq`, but never
+/// returns if the states never repeat.
+pub fn brent<S, F>(initial: S, mut next: F) -> (usize, usize)
+where
+    S: Clone + PartialEq,
+    F: FnMut(&S) -> S,
+{
+    let mut power = 1;
+    let mut period = 1;
+    let mut tortoise = initial.clone();
+    let mut hare = next(&initial);
+    while tortoise != hare {
+        if power == period {
+            tortoise = hare.clone();
+            power *= 2;
+            period = 0;
+        }
+        hare = next(&hare);
+        period += 1;
+    }
+
+    let mut tortoise = initial.clone();
+    let mut hare = initial;
+    for _ in 0..period {
+        hare = next(&hare);
+    }
+    let mut start = 0;
+    while tortoise != hare {
+        tortoise = next(&tortoise);
+        hare = next(&hare);
+        start += 1;
+    }
+
+    (start, period)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 0 -> 1 -> 2 -> ... -> 7 -> 2: a tail of two states into a cycle of six.
+    fn rho(s: &u32) -> u32 {
+        if *s < 7 {
+            s + 1
+        } else {
+            2
+        }
+    }
+
+    #[test]
+    fn find_cycle_and_brent_agree() {
+        let cycle = find_cycle(0, rho, 100).unwrap();
+        assert_eq!((cycle.start, cycle.period), (2, 6));
+        assert_eq!(brent(0, rho), (2, 6));
+        assert_eq!(cycle.states(), &[0, 1, 2, 3, 4, 5, 6, 7]);
+    }
+
+    #[test]
+    fn state_at_past_the_cycle() {
+        let cycle = find_cycle(0, rho, 100).unwrap();
+        let mut state = 0;
+        for step in 0..50 {
+            assert_eq!(*cycle.state_at(step), state, "step {}", step);
+            state = rho(&state);
+        }
+        assert_eq!(*cycle.state_at(1_000_000_000), 2 + (1_000_000_000 - 2) % 6);
+    }
+
+    #[test]
+    fn find_cycle_gives_up_after_max_steps() {
+        // The first repeat is the state after 8 steps.
+        assert!(find_cycle(0, rho, 7).is_none());
+        assert!(find_cycle(0, rho, 8).is_some());
+    }
+}
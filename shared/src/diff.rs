@@ -0,0 +1,64 @@
+use crate::Grid;
+use std::fmt::Write;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CellDiff<'a, T> {
+    pub x: usize,
+    pub y: usize,
+    pub old: &'a T,
+    pub new: &'a T,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Highlight {
+    /// Wraps changed cells in ANSI reverse-video escapes, for terminals.
+    Ansi,
+    /// Surrounds changed cells with `[` and `]` and pads the rest, for logs
+    /// and diffing tools.
+    Brackets,
+}
+
+impl<T: Clone + PartialEq> Grid<T> {
+    /// The cells whose value differs between `self` (old) and `other` (new),
+    /// in the same order as `iter`.
+    pub fn diff<'a>(&'a self, other: &'a Grid<T>) -> Vec<CellDiff<'a, T>> {
+        assert!(
+            self.width() == other.width() && self.height() == other.height(),
+            "cannot diff grids of different sizes"
+        );
+
+        self.iter()
+            .zip(other.cells.iter())
+            .filter(|((_, _, old), new)| old != new)
+            .map(|((x, y, old), new)| CellDiff { x, y, old, new })
+            .collect()
+    }
+
+    /// Renders `other` row by row with `cell_fn`, highlighting the cells that
+    /// changed since `self`.
+    pub fn render_diff<F>(&self, other: &Grid<T>, highlight: Highlight, cell_fn: F) -> String
+    where
+        F: Fn(&T) -> String,
+    {
+        assert!(
+            self.width() == other.width() && self.height() == other.height(),
+            "cannot diff grids of different sizes"
+        );
+
+        let mut out = String::new();
+        for ((x, y, old), new) in self.iter().zip(other.cells.iter()) {
+            if x == 0 && y > 0 {
+                out.push('\n');
+            }
+            let cell = cell_fn(new);
+            let changed = old != new;
+            let _ = match (highlight, changed) {
+                (Highlight::Ansi, true) => write!(out, "\x1b[7m{}\x1b[0m", cell),
+                (Highlight::Ansi, false) => write!(out, "{}", cell),
+                (Highlight::Brackets, true) => write!(out, "[{}]", cell),
+                (Highlight::Brackets, false) => write!(out, " {} ", cell),
+            };
+        }
+        out
+    }
+}
@@ -0,0 +1,261 @@
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct Grid3<T> {
+    width: usize,
+    height: usize,
+    depth: usize,
+    pub cells: Vec<T>,
+}
+
+impl<T: Clone> Grid3<T> {
+    pub fn new(width: usize, height: usize, depth: usize, initial_value: T) -> Grid3<T> {
+        Grid3 {
+            width,
+            height,
+            depth,
+            cells: vec![initial_value; width * height * depth],
+        }
+    }
+
+    pub fn new_cube(size: usize, initial_value: T) -> Grid3<T> {
+        Grid3::new(size, size, size, initial_value)
+    }
+
+    pub fn new_cube_with_value_fn<F>(size: usize, cell_value_fn: F) -> Grid3<T>
+    where
+        F: Fn(usize, usize, usize) -> T,
+    {
+        let mut g = Grid3 {
+            width: size,
+            height: size,
+            depth: size,
+            cells: Vec::with_capacity(size * size * size),
+        };
+        for z in 0..size {
+            for y in 0..size {
+                for x in 0..size {
+                    g.cells.push(cell_value_fn(x, y, z));
+                }
+            }
+        }
+        g
+    }
+
+    /// Builds a cube from `size` layers, each a list of rows.
+    pub fn new_cube_with_layers(size: usize, layers: Vec<Vec<Vec<T>>>) -> Grid3<T> {
+        let mut cells = Vec::new();
+        for layer in layers {
+            for mut row in layer {
+                cells.append(&mut row);
+            }
+        }
+        Grid3 {
+            width: size,
+            height: size,
+            depth: size,
+            cells,
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    pub fn get(&self, x: usize, y: usize, z: usize) -> &T {
+        &self.cells[self.cell_offset(x, y, z)]
+    }
+
+    pub fn get_mut(&mut self, x: usize, y: usize, z: usize) -> &mut T {
+        let offset = self.cell_offset(x, y, z);
+        &mut self.cells[offset]
+    }
+
+    pub fn try_get(&self, x: isize, y: isize, z: isize) -> Option<&T> {
+        self.checked_coords(x, y, z)
+            .map(|(x, y, z)| self.get(x, y, z))
+    }
+
+    pub fn try_get_mut(&mut self, x: isize, y: isize, z: isize) -> Option<&mut T> {
+        self.checked_coords(x, y, z)
+            .map(move |(x, y, z)| self.get_mut(x, y, z))
+    }
+
+    pub fn iter(&self) -> Grid3Iter<'_, T> {
+        Grid3Iter {
+            grid: self,
+            x: 0,
+            y: 0,
+            z: 0,
+        }
+    }
+
+    /// Iterates over the up to 26 cells surrounding `(x, y, z)`.
+    pub fn neighbours(&self, x: usize, y: usize, z: usize) -> Grid3Neighbours<'_, T> {
+        Grid3Neighbours {
+            grid: self,
+            center: (x as isize, y as isize, z as isize),
+            offsets: &NEIGHBOUR_OFFSETS_26,
+            next: 0,
+        }
+    }
+
+    /// Iterates over the up to 6 cells sharing a face with `(x, y, z)`.
+    pub fn face_neighbours(&self, x: usize, y: usize, z: usize) -> Grid3Neighbours<'_, T> {
+        Grid3Neighbours {
+            grid: self,
+            center: (x as isize, y as isize, z as isize),
+            offsets: &NEIGHBOUR_OFFSETS_6,
+            next: 0,
+        }
+    }
+
+    fn checked_coords(&self, x: isize, y: isize, z: isize) -> Option<(usize, usize, usize)> {
+        if x < 0 || y < 0 || z < 0 {
+            None
+        } else {
+            let (x, y, z) = (x as usize, y as usize, z as usize);
+            if x >= self.width || y >= self.height || z >= self.depth {
+                None
+            } else {
+                Some((x, y, z))
+            }
+        }
+    }
+
+    fn cell_offset(&self, x: usize, y: usize, z: usize) -> usize {
+        assert!(
+            x < self.width && y < self.height && z < self.depth,
+            "coordinate out of bounds"
+        );
+        (z * self.height + y) * self.width + x
+    }
+}
+
+const NEIGHBOUR_OFFSETS_6: [(isize, isize, isize); 6] = [
+    (0, 0, -1),
+    (0, -1, 0),
+    (-1, 0, 0),
+    (1, 0, 0),
+    (0, 1, 0),
+    (0, 0, 1),
+];
+
+const NEIGHBOUR_OFFSETS_26: [(isize, isize, isize); 26] = {
+    let mut offsets = [(0, 0, 0); 26];
+    let mut i = 0;
+    let mut dz = -1;
+    while dz <= 1 {
+        let mut dy = -1;
+        while dy <= 1 {
+            let mut dx = -1;
+            while dx <= 1 {
+                if dx != 0 || dy != 0 || dz != 0 {
+                    offsets[i] = (dx, dy, dz);
+                    i += 1;
+                }
+                dx += 1;
+            }
+            dy += 1;
+        }
+        dz += 1;
+    }
+    offsets
+};
+
+pub struct Grid3Iter<'a, T> {
+    grid: &'a Grid3<T>,
+    x: usize,
+    y: usize,
+    z: usize,
+}
+
+impl<'a, T: Clone> Iterator for Grid3Iter<'a, T> {
+    type Item = (usize, usize, usize, &'a T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.z < self.grid.depth && self.grid.width > 0 && self.grid.height > 0 {
+            let (x, y, z) = (self.x, self.y, self.z);
+            let val = self.grid.get(x, y, z);
+            self.x += 1;
+            if self.x == self.grid.width {
+                self.x = 0;
+                self.y += 1;
+                if self.y == self.grid.height {
+                    self.y = 0;
+                    self.z += 1;
+                }
+            }
+            Some((x, y, z, val))
+        } else {
+            None
+        }
+    }
+}
+
+pub struct Grid3Neighbours<'a, T> {
+    grid: &'a Grid3<T>,
+    center: (isize, isize, isize),
+    offsets: &'static [(isize, isize, isize)],
+    next: usize,
+}
+
+impl<'a, T: Clone> Iterator for Grid3Neighbours<'a, T> {
+    type Item = (usize, usize, usize, &'a T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(&(dx, dy, dz)) = self.offsets.get(self.next) {
+            self.next += 1;
+            let (x, y, z) = (self.center.0 + dx, self.center.1 + dy, self.center.2 + dz);
+            if let Some((x, y, z)) = self.grid.checked_coords(x, y, z) {
+                return Some((x, y, z, self.grid.get(x, y, z)));
+            }
+        }
+        None
+    }
+}
+
+impl<T: Clone> std::ops::Index<(usize, usize, usize)> for Grid3<T> {
+    type Output = T;
+
+    fn index(&self, coords: (usize, usize, usize)) -> &T {
+        let (x, y, z) = coords;
+        self.get(x, y, z)
+    }
+}
+
+impl<T: Clone> std::ops::IndexMut<(usize, usize, usize)> for Grid3<T> {
+    fn index_mut(&mut self, coords: (usize, usize, usize)) -> &mut T {
+        let (x, y, z) = coords;
+        self.get_mut(x, y, z)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn neighbour_counts() {
+        let g = Grid3::new(3, 3, 3, 0);
+        assert_eq!(g.neighbours(1, 1, 1).count(), 26);
+        assert_eq!(g.face_neighbours(1, 1, 1).count(), 6);
+        assert_eq!(g.neighbours(0, 0, 0).count(), 7);
+        assert_eq!(g.face_neighbours(0, 0, 0).count(), 3);
+        assert_eq!(g.neighbours(2, 2, 2).count(), 7);
+        assert_eq!(g.face_neighbours(2, 2, 2).count(), 3);
+    }
+
+    #[test]
+    #[should_panic(expected = "coordinate out of bounds")]
+    fn get_past_the_end_of_a_row_panics() {
+        let g = Grid3::new(3, 2, 2, 0);
+        g.get(3, 0, 0);
+    }
+}
@@ -0,0 +1,234 @@
+/// A grid with `D` dimensions. Coordinates are given as `[usize; D]`, with
+/// the first coordinate varying fastest, so `GridN<T, 2>` iterates in the same
+/// order as `Grid<T>` and `GridN<T, 3>` in the same order as `Grid3<T>`.
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct GridN<T, const D: usize> {
+    dims: [usize; D],
+    pub cells: Vec<T>,
+}
+
+impl<T: Clone, const D: usize> GridN<T, D> {
+    pub fn new(dims: [usize; D], initial_value: T) -> GridN<T, D> {
+        GridN {
+            dims,
+            cells: vec![initial_value; dims.iter().product()],
+        }
+    }
+
+    pub fn new_cube(size: usize, initial_value: T) -> GridN<T, D> {
+        GridN::new([size; D], initial_value)
+    }
+
+    pub fn new_cube_with_value_fn<F>(size: usize, cell_value_fn: F) -> GridN<T, D>
+    where
+        F: Fn([usize; D]) -> T,
+    {
+        let dims = [size; D];
+        let len = dims.iter().product();
+        let mut g = GridN {
+            dims,
+            cells: Vec::with_capacity(len),
+        };
+        for offset in 0..len {
+            g.cells.push(cell_value_fn(g.coords_of(offset)));
+        }
+        g
+    }
+
+    pub fn dims(&self) -> [usize; D] {
+        self.dims
+    }
+
+    pub fn get(&self, coords: [usize; D]) -> &T {
+        &self.cells[self.cell_offset(coords)]
+    }
+
+    pub fn get_mut(&mut self, coords: [usize; D]) -> &mut T {
+        let offset = self.cell_offset(coords);
+        &mut self.cells[offset]
+    }
+
+    pub fn try_get(&self, coords: [isize; D]) -> Option<&T> {
+        self.checked_coords(coords).map(|c| self.get(c))
+    }
+
+    pub fn try_get_mut(&mut self, coords: [isize; D]) -> Option<&mut T> {
+        self.checked_coords(coords).map(move |c| self.get_mut(c))
+    }
+
+    pub fn iter(&self) -> GridNIter<'_, T, D> {
+        GridNIter {
+            grid: self,
+            offset: 0,
+        }
+    }
+
+    /// Iterates over the up to `3^D - 1` cells surrounding `coords`.
+    pub fn neighbours(&self, coords: [usize; D]) -> GridNNeighbours<'_, T, D> {
+        GridNNeighbours {
+            grid: self,
+            center: coords,
+            faces_only: false,
+            next: 0,
+        }
+    }
+
+    /// Iterates over the up to `2 * D` cells that differ from `coords` along a
+    /// single axis.
+    pub fn face_neighbours(&self, coords: [usize; D]) -> GridNNeighbours<'_, T, D> {
+        GridNNeighbours {
+            grid: self,
+            center: coords,
+            faces_only: true,
+            next: 0,
+        }
+    }
+
+    fn checked_coords(&self, coords: [isize; D]) -> Option<[usize; D]> {
+        let mut checked = [0; D];
+        for (i, &c) in coords.iter().enumerate() {
+            if c < 0 || c as usize >= self.dims[i] {
+                return None;
+            }
+            checked[i] = c as usize;
+        }
+        Some(checked)
+    }
+
+    fn cell_offset(&self, coords: [usize; D]) -> usize {
+        coords
+            .iter()
+            .zip(self.dims.iter())
+            .rev()
+            .fold(0, |acc, (&c, &dim)| {
+                assert!(c < dim, "coordinate out of bounds");
+                acc * dim + c
+            })
+    }
+
+    fn coords_of(&self, mut offset: usize) -> [usize; D] {
+        let mut coords = [0; D];
+        for (c, &dim) in coords.iter_mut().zip(self.dims.iter()) {
+            *c = offset % dim;
+            offset /= dim;
+        }
+        coords
+    }
+}
+
+pub struct GridNIter<'a, T, const D: usize> {
+    grid: &'a GridN<T, D>,
+    offset: usize,
+}
+
+impl<'a, T: Clone, const D: usize> Iterator for GridNIter<'a, T, D> {
+    type Item = ([usize; D], &'a T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let val = self.grid.cells.get(self.offset)?;
+        let coords = self.grid.coords_of(self.offset);
+        self.offset += 1;
+        Some((coords, val))
+    }
+}
+
+pub struct GridNNeighbours<'a, T, const D: usize> {
+    grid: &'a GridN<T, D>,
+    center: [usize; D],
+    faces_only: bool,
+    next: usize,
+}
+
+impl<'a, T, const D: usize> GridNNeighbours<'a, T, D> {
+    /// Decodes offset number `n` into a `-1..=1` delta per axis, or `None`
+    /// once every offset has been produced.
+    fn delta(&self, n: usize) -> Option<[isize; D]> {
+        let mut delta = [0; D];
+        if self.faces_only {
+            if n >= 2 * D {
+                return None;
+            }
+            delta[n / 2] = [-1, 1][n % 2];
+        } else {
+            let mut n = n;
+            for d in delta.iter_mut() {
+                *d = (n % 3) as isize - 1;
+                n /= 3;
+            }
+            if n > 0 {
+                return None;
+            }
+        }
+        Some(delta)
+    }
+}
+
+impl<'a, T: Clone, const D: usize> Iterator for GridNNeighbours<'a, T, D> {
+    type Item = ([usize; D], &'a T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(delta) = self.delta(self.next) {
+            self.next += 1;
+            if delta.iter().all(|&d| d == 0) {
+                continue;
+            }
+            let mut coords = [0; D];
+            for i in 0..D {
+                coords[i] = self.center[i] as isize + delta[i];
+            }
+            if let Some(c) = self.grid.checked_coords(coords) {
+                return Some((c, self.grid.get(c)));
+            }
+        }
+        None
+    }
+}
+
+impl<T: Clone, const D: usize> std::ops::Index<[usize; D]> for GridN<T, D> {
+    type Output = T;
+
+    fn index(&self, coords: [usize; D]) -> &T {
+        self.get(coords)
+    }
+}
+
+impl<T: Clone, const D: usize> std::ops::IndexMut<[usize; D]> for GridN<T, D> {
+    fn index_mut(&mut self, coords: [usize; D]) -> &mut T {
+        self.get_mut(coords)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Grid, Grid3};
+
+    #[test]
+    fn iterates_like_grid() {
+        let n = GridN::<_, 2>::new_cube_with_value_fn(4, |[x, y]| x * 10 + y);
+        let g = Grid::new_square_with_value_fn(4, |x, y| x * 10 + y);
+        let from_n: Vec<_> = n.iter().map(|([x, y], &v)| (x, y, v)).collect();
+        let from_g: Vec<_> = g.iter().map(|(x, y, &v)| (x, y, v)).collect();
+        assert_eq!(from_n, from_g);
+    }
+
+    #[test]
+    fn iterates_like_grid3() {
+        let n = GridN::<_, 3>::new_cube_with_value_fn(3, |[x, y, z]| x * 100 + y * 10 + z);
+        let g = Grid3::new_cube_with_value_fn(3, |x, y, z| x * 100 + y * 10 + z);
+        let from_n: Vec<_> = n.iter().map(|([x, y, z], &v)| (x, y, z, v)).collect();
+        let from_g: Vec<_> = g.iter().map(|(x, y, z, &v)| (x, y, z, v)).collect();
+        assert_eq!(from_n, from_g);
+    }
+
+    #[test]
+    fn neighbour_counts() {
+        let g = GridN::<_, 3>::new_cube(3, 0);
+        assert_eq!(g.neighbours([1, 1, 1]).count(), 26);
+        assert_eq!(g.face_neighbours([1, 1, 1]).count(), 6);
+        assert_eq!(g.neighbours([0, 0, 0]).count(), 7);
+        assert_eq!(g.face_neighbours([0, 0, 0]).count(), 3);
+        assert_eq!(g.neighbours([2, 0, 2]).count(), 7);
+        assert_eq!(g.face_neighbours([2, 0, 2]).count(), 3);
+    }
+}
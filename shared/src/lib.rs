@@ -1,6 +1,20 @@
 use std::convert::AsMut;
 
-#[derive(Clone)]
+mod automaton;
+mod bitgrid;
+mod cycle;
+mod diff;
+mod grid3;
+mod gridn;
+
+pub use automaton::{Automaton, Neighbourhood, StepStats};
+pub use bitgrid::{BitGrid, BitGridIter};
+pub use cycle::{brent, find_cycle, Cycle};
+pub use diff::{CellDiff, Highlight};
+pub use grid3::{Grid3, Grid3Iter, Grid3Neighbours};
+pub use gridn::{GridN, GridNIter, GridNNeighbours};
+
+#[derive(Clone, PartialEq, Eq, Hash)]
 pub struct Grid<T> {
     width: usize,
     height: usize,
@@ -98,9 +112,43 @@ impl<T: Clone> Grid<T> {
         }
     }
 
-    pub fn iter(&self) -> GridIter<T> {
+    /// Maps any signed coordinates onto the grid, treating it as a torus.
+    pub fn wrap_coords(&self, x: isize, y: isize) -> (usize, usize) {
+        (
+            x.rem_euclid(self.width as isize) as usize,
+            y.rem_euclid(self.height as isize) as usize,
+        )
+    }
+
+    pub fn get_wrapping(&self, x: isize, y: isize) -> &T {
+        let (x, y) = self.wrap_coords(x, y);
+        self.get(x, y)
+    }
+
+    pub fn get_wrapping_mut(&mut self, x: isize, y: isize) -> &mut T {
+        let (x, y) = self.wrap_coords(x, y);
+        self.get_mut(x, y)
+    }
+
+    /// Iterates over the cells at each of `offsets` from `(x, y)`, wrapping
+    /// around the edges, e.g. with `Neighbourhood::Moore.offsets()`.
+    pub fn wrapping_neighbours<'a>(
+        &'a self,
+        x: usize,
+        y: usize,
+        offsets: &'a [(isize, isize)],
+    ) -> WrappingNeighbours<'a, T> {
+        WrappingNeighbours {
+            grid: self,
+            x: x as isize,
+            y: y as isize,
+            offsets: offsets.iter(),
+        }
+    }
+
+    pub fn iter(&self) -> GridIter<'_, T> {
         GridIter {
-            grid: &self,
+            grid: self,
             x: 0,
             y: 0,
         }
@@ -155,6 +203,23 @@ impl<'a, T: Clone> Iterator for GridIter<'a, T> {
     }
 }
 
+pub struct WrappingNeighbours<'a, T> {
+    grid: &'a Grid<T>,
+    x: isize,
+    y: isize,
+    offsets: std::slice::Iter<'a, (isize, isize)>,
+}
+
+impl<'a, T: Clone> Iterator for WrappingNeighbours<'a, T> {
+    type Item = (usize, usize, &'a T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let &(dx, dy) = self.offsets.next()?;
+        let (x, y) = self.grid.wrap_coords(self.x + dx, self.y + dy);
+        Some((x, y, self.grid.get(x, y)))
+    }
+}
+
 impl<T: Clone> std::ops::Index<(usize, usize)> for Grid<T> {
     type Output = T;
 